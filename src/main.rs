@@ -1,55 +1,16 @@
 use macroquad::prelude::*;
-use macroquad::rand::gen_range;
+use sketch::bloom::Bloom;
+use sketch::*;
+use std::sync::Arc;
 
 // cargo run --release
+// cargo run --release -- --threaded
 // cargo build --release --target wasm32-unknown-unknown
 // basic-http-server target/wasm32-unknown-unknown/release
 // zip target/wasm32-unknown-unknown/release.zip -j target/wasm32-unknown-unknown/release/*
 // butler push target/wasm32-unknown-unknown/release.zip aaratha/rope:html5
 // butler status aaratha/rope:html5
 
-const ROPE_THICKNESS: f32 = 2.0;
-const ROPE_BALL_RADIUS: f32 = 7.0;
-const ROPE_COLOR: Color = Color::new(0.7, 0.8, 1.0, 1.0);
-const SEGMENT_LENGTH: f32 = 10.0;
-const CONSTRAINT_ITERATIONS: usize = 8;
-
-const TIME_STEP: f32 = 0.016;
-const FRICTION: f32 = 0.98;
-const SUBSTEPS: usize = 5;
-const LERP_FACTOR: f32 = 0.5;
-
-const ENEMY_SPEED: f32 = 7.0;
-const ENEMY_SPAWN_INTERVAL: f32 = 2.0; // in seconds
-const ENEMY_RADIUS: f32 = 10.0;
-
-const POINT_SPAWN_INTERVAL: f32 = 1.0; // in seconds
-const MAX_POINTS: usize = 20;
-const POINT_RADIUS: f32 = 5.0;
-
-const BORDER_THICKNESS: f32 = 5.0;
-const BORDER_COLOR: Color = Color::new(1.0, 1.0, 1.0, 0.0); // Adjust border color as needed
-
-#[derive(Clone, Copy)]
-struct Frame {
-    width: f32,
-    height: f32,
-}
-
-impl Frame {
-    fn new() -> Self {
-        Self {
-            width: screen_width(),
-            height: screen_height(),
-        }
-    }
-
-    fn update(&mut self) {
-        self.width = screen_width();
-        self.height = screen_height();
-    }
-}
-
 struct FpsCounter {
     last_update: f32,
     fps: f32,
@@ -81,325 +42,485 @@ impl FpsCounter {
     }
 }
 
-#[derive(Clone, Copy, PartialEq)]
-struct Particle {
-    position: Vec2,
-    old_position: Vec2,
-    acceleration: Vec2,
-    friction: f32,
-}
-
-impl Particle {
-    fn new(position: Vec2) -> Self {
-        Self {
-            position,
-            old_position: position,
-            acceleration: Vec2::ZERO,
-            friction: FRICTION,
-        }
+const TUTORIAL_LINES: &[&str] = &[
+    "Move the mouse to drag the rope around.",
+    "Collect the yellow points, avoid the enemies.",
+    "",
+    "S: subdivide rope   J: toggle solver",
+    "I: toggle integrator M: fleeing points",
+    "B: toggle shield    E: cycle anchor easing",
+    "F: fit to window    H: collision heatmap",
+    "G: speed glow       Space: crack the whip",
+    "T: mesh rendering   K: debug skeleton",
+    "A: alternate solver sweep O: closed blob",
+    "U: magnetic tip     D: dynamic stiffness",
+    "V: danger vignette  hold R: rewind",
+    "P: anchor trail     C: high-contrast colours",
+    "X: entity glyphs    L: sticky rope",
+    "N: bloom            Z: hang at rest when idle",
+    "W: walls/wrapping   Y: sandbox (no spawns)",
+    "Q: letterbox arena  Tab: leaderboard",
+    "Click: flick the rope   2: cursor smoothing",
+    "1: motion blur      F12: screenshot",
+    "`: console (try 'help')",
+    "",
+    "Press any key to start",
+];
+
+fn draw_tutorial() {
+    let width = 520.;
+    let height = 60. + TUTORIAL_LINES.len() as f32 * 28.;
+    let x = (screen_width() - width) / 2.;
+    let y = (screen_height() - height) / 2.;
+    draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.75));
+    draw_rectangle_lines(x, y, width, height, 2., WHITE);
+    draw_text("How to play", x + 20., y + 40., 36., WHITE);
+    for (i, line) in TUTORIAL_LINES.iter().enumerate() {
+        draw_text(line, x + 20., y + 80. + i as f32 * 28., 24., WHITE);
     }
+}
 
-    fn update(&mut self) {
-        let mut velocity = self.position - self.old_position;
-        velocity *= self.friction; // Apply friction to the velocity
-        self.old_position = self.position;
-        self.position += velocity; // + self.acceleration * TIME_STEP * TIME_STEP;
-        self.acceleration = Vec2::ZERO; // Reset acceleration
-    }
+/// Title over the attract-mode demo, once the tutorial has been seen.
+fn draw_title() {
+    let title = "Rope";
+    let size = measure_text(title, None, 64, 1.0);
+    draw_text(
+        title,
+        (screen_width() - size.width) / 2.,
+        screen_height() / 2. - 40.,
+        64.,
+        WHITE,
+    );
+    let prompt = "Press any key to start";
+    let size = measure_text(prompt, None, 28, 1.0);
+    draw_text(
+        prompt,
+        (screen_width() - size.width) / 2.,
+        screen_height() / 2. + 10.,
+        28.,
+        LIGHTGRAY,
+    );
 }
 
-struct Rope {
-    particles: Vec<Particle>,
-    thickness: f32,
-    ball_radius: f32,
-    constraint_strength: f32,
+const MENU_OPTION_WIDTH: f32 = 200.;
+const MENU_OPTION_HEIGHT: f32 = 50.;
+const MENU_OPTION_GAP: f32 = 15.;
+
+/// Difficulty picker shown over the demo before a run: arrow keys and
+/// Enter, or hover and click.
+struct MenuState {
+    selected: usize,
 }
 
-impl Rope {
-    fn new(start: Vec2, num_particles: usize) -> Self {
-        let mut particles = Vec::with_capacity(num_particles);
-        for i in 0..num_particles {
-            particles.push(Particle::new(start + vec2(i as f32 * SEGMENT_LENGTH, 0.0)));
-        }
+impl MenuState {
+    fn new(current: Difficulty) -> Self {
         Self {
-            particles,
-            thickness: ROPE_THICKNESS,
-            ball_radius: ROPE_BALL_RADIUS,
-            constraint_strength: 0.5,
+            selected: Difficulty::ALL
+                .iter()
+                .position(|&difficulty| difficulty == current)
+                .unwrap_or(0),
         }
     }
 
-    fn update(&mut self, target: Vec2) {
-        self.particles[0].position = target;
-
-        for _ in 0..CONSTRAINT_ITERATIONS {
-            for i in 0..self.particles.len() - 1 {
-                let particle_a = self.particles[i];
-                let particle_b = self.particles[i + 1];
-                let delta = particle_b.position - particle_a.position;
-                let delta_length = delta.length();
-                let diff = (delta_length - SEGMENT_LENGTH) / delta_length;
-                let offset = delta * diff * self.constraint_strength / SUBSTEPS as f32;
+    fn option_rect(index: usize) -> Rect {
+        let count = Difficulty::ALL.len() as f32;
+        let height = count * MENU_OPTION_HEIGHT + (count - 1.) * MENU_OPTION_GAP;
+        Rect::new(
+            (screen_width() - MENU_OPTION_WIDTH) / 2.,
+            (screen_height() - height) / 2. + index as f32 * (MENU_OPTION_HEIGHT + MENU_OPTION_GAP),
+            MENU_OPTION_WIDTH,
+            MENU_OPTION_HEIGHT,
+        )
+    }
 
-                if i != 0 {
-                    self.particles[i].position += offset;
-                }
-                self.particles[i + 1].position -= offset;
-            }
+    /// Moves the selection with this frame's input and returns the
+    /// difficulty picked, if any.
+    fn update(&mut self) -> Option<Difficulty> {
+        let count = Difficulty::ALL.len();
+        if is_key_pressed(KeyCode::Up) {
+            self.selected = (self.selected + count - 1) % count;
         }
-
-        for i in 1..self.particles.len() {
-            self.particles[i].update();
+        if is_key_pressed(KeyCode::Down) {
+            self.selected = (self.selected + 1) % count;
         }
-    }
-
-    fn extend(&mut self) {
-        let last_particle = self.particles.last().unwrap();
-        let direction = last_particle.position - self.particles[self.particles.len() - 2].position;
-        let new_particle = Particle::new(last_particle.position + direction);
-        self.particles.push(new_particle);
+        if is_key_pressed(KeyCode::Enter) {
+            return Some(Difficulty::ALL[self.selected]);
+        }
+        let mouse: Vec2 = mouse_position().into();
+        let hovered = (0..count).find(|&i| Self::option_rect(i).contains(mouse))?;
+        self.selected = hovered;
+        is_mouse_button_pressed(MouseButton::Left).then_some(Difficulty::ALL[hovered])
     }
 
     fn draw(&self) {
-        for i in 0..self.particles.len() - 1 {
-            draw_line(
-                self.particles[i].position.x,
-                self.particles[i].position.y,
-                self.particles[i + 1].position.x,
-                self.particles[i + 1].position.y,
-                self.thickness,
+        let title = "Choose a difficulty";
+        let size = measure_text(title, None, 36, 1.0);
+        let top = Self::option_rect(0).y;
+        draw_text(
+            title,
+            (screen_width() - size.width) / 2.,
+            top - 30.,
+            36.,
+            WHITE,
+        );
+        for (i, difficulty) in Difficulty::ALL.iter().enumerate() {
+            let rect = Self::option_rect(i);
+            let color = if i == self.selected { BLUE } else { DARKGRAY };
+            draw_rectangle(rect.x, rect.y, rect.w, rect.h, color);
+            let name = difficulty.name();
+            let size = measure_text(name, None, 30, 1.0);
+            draw_text(
+                name,
+                rect.x + (rect.w - size.width) / 2.,
+                rect.y + 33.,
+                30.,
                 WHITE,
             );
         }
-        draw_circle(
-            self.particles[0].position.x,
-            self.particles[0].position.y,
-            self.ball_radius,
-            WHITE,
-        );
-        draw_circle(
-            self.particles[self.particles.len() - 1].position.x,
-            self.particles[self.particles.len() - 1].position.y,
-            self.ball_radius,
-            WHITE,
+    }
+}
+
+/// `timestamp`, in seconds since the Unix epoch, as a UTC `YYYY-MM-DD` date.
+fn format_date(timestamp: f64) -> String {
+    // Days to civil date, from Howard Hinnant's `civil_from_days`.
+    let days = (timestamp / 86_400.0).floor() as i64 + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days.rem_euclid(146_097);
+    let year_of_era =
+        (day_of_era - day_of_era / 1_460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+fn draw_leaderboard(leaderboard: &Leaderboard) {
+    let width = 360.;
+    let rows = leaderboard.entries.len().max(1);
+    let height = 80. + rows as f32 * 28.;
+    let x = (screen_width() - width) / 2.;
+    let y = (screen_height() - height) / 2.;
+    draw_rectangle(x, y, width, height, Color::new(0.0, 0.0, 0.0, 0.75));
+    draw_rectangle_lines(x, y, width, height, 2., WHITE);
+    draw_text("Leaderboard", x + 20., y + 40., 36., WHITE);
+    if leaderboard.entries.is_empty() {
+        draw_text("No runs yet", x + 20., y + 80., 24., LIGHTGRAY);
+    }
+    for (i, entry) in leaderboard.entries.iter().enumerate() {
+        let line = format!(
+            "{:>2}. {:>6}   {}",
+            i + 1,
+            entry.score,
+            format_date(entry.timestamp)
         );
+        draw_text(&line, x + 20., y + 80. + i as f32 * 28., 24., WHITE);
     }
 }
 
-struct Enemy {
-    particle: Particle,
-    active: bool,
-    radius: f32,
+/// Screen position steering the anchor: the first active touch on touch
+/// screens, otherwise the mouse.
+fn pointer_position() -> Vec2 {
+    touches()
+        .iter()
+        .find(|touch| touch.phase != TouchPhase::Ended && touch.phase != TouchPhase::Cancelled)
+        .map_or_else(|| mouse_position().into(), |touch| touch.position)
 }
 
-impl Enemy {
-    fn new(frame: Frame) -> Self {
-        let pos = if gen_range(0., 1.) > 0.5 {
-            // Spawn on the left or right side of the rectangle
-            Vec2::new(
-                if gen_range(0., 1.) > 0.5 {
-                    (screen_width() - frame.width) / 2.
-                } else {
-                    (screen_width() + frame.width) / 2.
-                },
-                rand::gen_range(
-                    (screen_height() - frame.height) / 2.,
-                    (screen_height() + frame.height) / 2.,
-                ),
-            )
+type KeyAction = fn(&mut Game);
+
+/// Gameplay key bindings, ignored while the console has focus. `F` is
+/// handled in the main loop since it reads the window size.
+const KEY_BINDINGS: &[(KeyCode, KeyAction)] = &[
+    (KeyCode::S, |game| game.rope.subdivide()),
+    (KeyCode::Key1, |game| game.motion_blur = !game.motion_blur),
+    (KeyCode::J, |game| {
+        game.rope.solver = match game.rope.solver {
+            SolverKind::GaussSeidel => SolverKind::Jacobi,
+            SolverKind::Jacobi => SolverKind::GaussSeidel,
+        };
+    }),
+    (KeyCode::B, |game| game.toggle_shield()),
+    (KeyCode::H, |game| game.show_heatmap = !game.show_heatmap),
+    (KeyCode::E, |game| {
+        game.anchor_easing = game.anchor_easing.next()
+    }),
+    (KeyCode::G, |game| {
+        game.rope.speed_glow = if game.rope.speed_glow > 0.0 {
+            0.0
         } else {
-            // Spawn on the top or bottom side of the rectangle
-            Vec2::new(
-                rand::gen_range(
-                    (screen_width() - frame.width) / 2.,
-                    (screen_width() + frame.width) / 2.,
-                ),
-                if gen_range(0., 1.) > 0.5 {
-                    (screen_height() - frame.height) / 2.
-                } else {
-                    (screen_height() + frame.height) / 2.
-                },
-            )
+            SPEED_GLOW_STRENGTH
         };
-        Self {
-            particle: Particle::new(pos),
-            active: true,
-            radius: ENEMY_RADIUS,
-        }
-    }
-
-    fn update(&mut self, target: Vec2, frame: Frame) {
-        let direction = target - self.particle.position;
-        let distance = direction.length();
-        if distance > 0.0 {
-            let step = direction.normalize() * ENEMY_SPEED * TIME_STEP;
-            self.particle.position += step;
-        }
-        self.particle.update();
-        if !is_in_frame(&self.particle, frame) {
-            self.active = false;
-        }
-    }
+    }),
+    (KeyCode::Space, |game| game.crack_whip()),
+    (KeyCode::I, |game| {
+        game.rope.set_integrator(match game.rope.integrator {
+            Integrator::Verlet => Integrator::SemiImplicitEuler,
+            Integrator::SemiImplicitEuler => Integrator::Verlet,
+        });
+    }),
+    (KeyCode::M, |game| game.point_repel = !game.point_repel),
+    (KeyCode::T, |game| {
+        game.rope.mesh_render = !game.rope.mesh_render
+    }),
+    (KeyCode::K, |game| game.show_skeleton = !game.show_skeleton),
+    (KeyCode::A, |game| {
+        game.rope.alternate_sweep = !game.rope.alternate_sweep
+    }),
+    (KeyCode::O, |game| game.rope.closed = !game.rope.closed),
+    (KeyCode::D, |game| {
+        game.rope.dynamic_stiffness = !game.rope.dynamic_stiffness
+    }),
+    (KeyCode::V, |game| {
+        game.danger_vignette = !game.danger_vignette
+    }),
+    (KeyCode::C, |game| {
+        game.theme = if game.theme == Theme::HIGH_CONTRAST {
+            Theme::CLASSIC
+        } else {
+            Theme::HIGH_CONTRAST
+        };
+    }),
+    (KeyCode::X, |game| game.show_glyphs = !game.show_glyphs),
+    (KeyCode::L, |game| game.rope.sticky = !game.rope.sticky),
+    (KeyCode::Z, |game| {
+        game.rope.rest_pose = match game.rope.rest_pose {
+            Some(_) => None,
+            None => Some(game.rope.hanging_pose()),
+        };
+    }),
+    (KeyCode::Y, |game| {
+        let enabled = !game.spawning_enabled;
+        game.set_spawning(enabled, !enabled);
+    }),
+    (KeyCode::P, |game| {
+        game.show_anchor_trail = !game.show_anchor_trail
+    }),
+    (KeyCode::U, |game| {
+        game.rope.tip_pickup_radius = if game.rope.tip_pickup_radius > 0.0 {
+            0.0
+        } else {
+            TIP_PICKUP_RADIUS
+        };
+    }),
+];
 
-    fn draw(&self) {
-        if self.active {
-            draw_circle(
-                self.particle.position.x,
-                self.particle.position.y,
-                self.radius,
-                ROPE_COLOR,
-            );
+fn handle_keys(runner: &mut Runner) {
+    for &(key, action) in KEY_BINDINGS {
+        if is_key_pressed(key) {
+            runner.edit(Box::new(action));
         }
     }
 }
 
-struct Point {
-    position: Vec2,
-    active: bool,
-    radius: f32,
+/// Owns the game on the main thread, or hands it to a worker thread when
+/// started with `--threaded`.
+enum Runner {
+    Local(Box<Game>),
+    #[cfg(not(target_arch = "wasm32"))]
+    Threaded(ThreadedGame),
 }
 
-impl Point {
-    fn new(frame: Frame) -> Self {
-        let pos = Vec2::new(
-            rand::gen_range(
-                (screen_width() - frame.width) / 2.,
-                (screen_width() + frame.width) / 2.,
-            ),
-            rand::gen_range(
-                (screen_height() - frame.height) / 2.,
-                (screen_height() + frame.height) / 2.,
-            ),
-        );
-        Self {
-            position: pos,
-            active: true,
-            radius: POINT_RADIUS,
+impl Runner {
+    fn new(game: Game) -> Self {
+        #[cfg(not(target_arch = "wasm32"))]
+        if std::env::args().any(|arg| arg == "--threaded") {
+            return Runner::Threaded(ThreadedGame::spawn(game));
         }
+        Runner::Local(Box::new(game))
     }
 
-    fn draw(&self) {
-        if self.active {
-            draw_circle(
-                self.position.x,
-                self.position.y,
-                self.radius,
-                Color::new(1.0, 0.8, 0.0, 1.0),
-            );
+    fn edit(&mut self, edit: GameEdit) {
+        match self {
+            Runner::Local(game) => edit(game),
+            #[cfg(not(target_arch = "wasm32"))]
+            Runner::Threaded(threaded) => threaded.edit(edit),
         }
     }
-}
 
-fn check_collisions(
-    rope: &mut Rope,
-    enemies: &mut [Enemy],
-    points: &mut Vec<Point>,
-    score: &mut i32,
-    game_over: &mut bool, // Pass by mutable reference
-) {
-    for _ in 0..SUBSTEPS {
-        let particle_0 = rope.particles[0].clone();
-        for particle in rope.particles.iter_mut() {
-            check_enemy_collisions_with_particle(particle, enemies, game_over, particle_0.position);
-            check_point_collisions_with_particle(particle, points, score);
+    fn step(&mut self, cursor: Vec2, dt: f32) {
+        match self {
+            Runner::Local(game) => game.step(cursor, dt),
+            #[cfg(not(target_arch = "wasm32"))]
+            Runner::Threaded(threaded) => threaded.step(cursor, dt),
         }
     }
-}
 
-fn check_enemy_collisions_with_particle(
-    particle: &mut Particle,
-    enemies: &mut [Enemy],
-    game_over: &mut bool,
-    player_position: Vec2,
-) {
-    for enemy in enemies.iter_mut() {
-        let dist = enemy.particle.position - particle.position;
-        let len = dist.length();
-        if len < ROPE_BALL_RADIUS + ENEMY_RADIUS {
-            let offset = (ROPE_BALL_RADIUS + ENEMY_RADIUS - len) * dist.normalize();
-            enemy.particle.position += offset * 0.5;
-            particle.position -= offset * 0.5;
-            if particle.position == player_position {
-                *game_over = true; // Dereference and modify the original game_over
-            }
+    /// Reads the game, or the last completed step when threaded.
+    fn view<R>(&self, read: impl FnOnce(&Game) -> R) -> R {
+        match self {
+            Runner::Local(game) => read(game),
+            #[cfg(not(target_arch = "wasm32"))]
+            Runner::Threaded(threaded) => read(&threaded.snapshot()),
         }
     }
 }
 
-fn check_point_collisions_with_particle(
-    particle: &mut Particle,
-    points: &mut Vec<Point>,
-    score: &mut i32,
-) {
-    for point in points.iter_mut() {
-        let dist = point.position - particle.position;
-        let len = dist.length();
-        if len < POINT_RADIUS + ENEMY_RADIUS {
-            point.active = false;
-            *score += 1;
+const CONSOLE_LOG_LINES: usize = 6;
+
+/// Backtick-toggled console for tweaking the live `Config`.
+struct Console {
+    open: bool,
+    input: String,
+    log: Vec<String>,
+}
+
+impl Console {
+    fn new() -> Self {
+        Self {
+            open: false,
+            input: String::new(),
+            log: Vec::new(),
         }
     }
-}
 
-fn check_enemy_collisions(enemies: &mut [Enemy]) {
-    for i in 0..enemies.len() {
-        for j in (i + 1)..enemies.len() {
-            let dist = enemies[j].particle.position - enemies[i].particle.position;
-            let len = dist.length();
-            if len < ENEMY_RADIUS * 2.0 {
-                let offset = (ENEMY_RADIUS * 2.0 - len) * dist.normalize();
-                enemies[i].particle.position -= offset * 0.5;
-                enemies[j].particle.position += offset * 0.5;
+    fn update(&mut self, config: &mut Config) {
+        while let Some(c) = get_char_pressed() {
+            if c != '`' && !c.is_control() {
+                self.input.push(c);
             }
         }
+        if is_key_pressed(KeyCode::Backspace) {
+            self.input.pop();
+        }
+        if is_key_pressed(KeyCode::Enter) {
+            let command = std::mem::take(&mut self.input);
+            let output = config.execute(&command);
+            self.print(format!("> {}", command));
+            self.print(output);
+        }
     }
-}
 
-fn draw_ring(rope: &Rope) {
-    let center = rope.particles[0].position;
-    let radius = 200.0; // Adjust the radius as needed
-    let color = Color::new(1.0, 1.0, 1.0, 0.5); // Adjust the color and alpha as needed
-    draw_circle_lines(center.x, center.y, radius, 2.0, color); // Adjust the line thickness as needed
-}
+    /// Adds a line to the log, dropping the oldest past `CONSOLE_LOG_LINES`.
+    fn print(&mut self, line: String) {
+        self.log.push(line);
+        let excess = self.log.len().saturating_sub(CONSOLE_LOG_LINES);
+        self.log.drain(..excess);
+    }
 
-fn is_in_frame(particle: &Particle, frame: Frame) -> bool {
-    let x = particle.position.x;
-    let y = particle.position.y;
-    x >= (screen_width() - frame.width) / 2.
-        && x <= (screen_width() + frame.width) / 2.
-        && y >= (screen_height() - frame.height) / 2.
-        && y <= (screen_height() + frame.height) / 2.
+    fn draw(&self) {
+        if !self.open {
+            return;
+        }
+        let line_height = 20.;
+        let height = (CONSOLE_LOG_LINES + 1) as f32 * line_height + 10.;
+        let top = screen_height() - height;
+        draw_rectangle(
+            0.,
+            top,
+            screen_width(),
+            height,
+            Color::new(0.0, 0.0, 0.0, 0.8),
+        );
+        for (i, line) in self.log.iter().enumerate() {
+            draw_text(
+                line,
+                10.,
+                top + line_height * (i + 1) as f32,
+                18.,
+                LIGHTGRAY,
+            );
+        }
+        draw_text(
+            &format!("] {}_", self.input),
+            10.,
+            screen_height() - 10.,
+            18.,
+            WHITE,
+        );
+    }
 }
 
 #[macroquad::main("Rope Simulation")]
 async fn main() {
-    let mut game_over = false;
-    let mut rope = Rope::new(vec2(0.0, 100.0), 10);
-    let mut enemies: Vec<Enemy> = Vec::new();
-    let mut points: Vec<Point> = Vec::new();
-    let mut last_spawn_time = get_time();
-    let mut last_point_spawn_time = get_time();
-    let mut score = 0;
-    let mut last_extended_score = 0;
-    let mut frame = Frame::new();
+    let mut frame = Frame::from_screen();
+    let mut game = Game::new(frame);
+    game.ghost = Recording::load(GHOST_FILE).map(Arc::new);
+    game.leaderboard = Leaderboard::load(LEADERBOARD_FILE);
+    let sprites = Sprites::load().await;
+    // Off by default; stays unavailable if the shaders don't compile.
+    let mut bloom = Bloom::new().ok();
+    let mut bloom_enabled = false;
+    let mut runner = Runner::new(game);
     let mut fps_counter = FpsCounter::new();
+    let mut console = Console::new();
+    let mut reported_recovery = None;
+    let mut show_tutorial = !tutorial_dismissed();
+    let mut show_leaderboard = false;
+    let mut smoother = TargetSmoother::default();
+    // The title screen plays itself until a key is pressed, then offers
+    // the difficulty menu over the same demo.
+    let mut demo = Some(DemoMode::new(frame));
+    let mut menu: Option<MenuState> = None;
+    let mut difficulty = Difficulty::default();
 
     loop {
+        #[cfg(not(target_arch = "wasm32"))]
+        let frame_start = get_time();
         fps_counter.update();
 
-        fps_counter.draw();
+        if let Some(demo_mode) = &mut demo {
+            let dt = demo_mode.game.config.frame_dt(get_frame_time());
+            demo_mode.step(dt);
+            if let Some(camera) = frame.camera() {
+                set_camera(&camera);
+            }
+            demo_mode.game.draw(&sprites);
+            set_default_camera();
+            frame.draw_letterbox();
+            if frame.update() {
+                demo_mode.game.frame = frame;
+                demo_mode.game.clamp_to_frame();
+            }
+            if let Some(menu_state) = &mut menu {
+                menu_state.draw();
+                if let Some(choice) = menu_state.update() {
+                    difficulty = choice;
+                    demo = None;
+                    menu = None;
+                    let config = choice.config();
+                    runner.edit(Box::new(move |game| {
+                        game.frame = frame;
+                        game.config = config;
+                        game.reset();
+                    }));
+                }
+            } else {
+                if show_tutorial {
+                    draw_tutorial();
+                } else {
+                    draw_title();
+                }
+                if get_last_key_pressed().is_some() {
+                    if show_tutorial {
+                        show_tutorial = false;
+                        dismiss_tutorial();
+                    }
+                    menu = Some(MenuState::new(difficulty));
+                }
+            }
+            fps_counter.draw();
+
+            next_frame().await;
+            continue;
+        }
 
-        if game_over {
+        if runner.view(|game| game.game_over) {
             clear_background(BLACK);
             draw_text(
-                &format!("Game Over!"),
+                "Game Over!",
                 screen_width() / 2. - 85.,
                 screen_height() / 2. - 50.,
                 40.,
                 WHITE,
             );
             draw_text(
-                &format!("Your score is: {}", score),
+                &format!("Your score is: {}", runner.view(|game| game.score)),
                 screen_width() / 2. - 140.,
                 screen_height() / 2. - 20.,
                 40.,
@@ -413,14 +534,15 @@ async fn main() {
                     && mouse_position.y <= screen_height() / 2. + 50.
                 {
                     // Reset the game
-                    game_over = false;
-                    rope = Rope::new(vec2(0.0, 100.0), 10);
-                    enemies.clear();
-                    points.clear();
-                    score = 0;
-                    last_spawn_time = get_time();
-                    last_point_spawn_time = get_time();
-                    last_extended_score = 0;
+                    runner.edit(Box::new(|game| game.reset()));
+                }
+                if mouse_position.x >= screen_width() / 2. - 100.
+                    && mouse_position.x <= screen_width() / 2. + 100.
+                    && mouse_position.y >= screen_height() / 2. + 60.
+                    && mouse_position.y <= screen_height() / 2. + 110.
+                {
+                    demo = Some(DemoMode::new(frame));
+                    menu = Some(MenuState::new(difficulty));
                 }
             }
 
@@ -440,78 +562,184 @@ async fn main() {
                 WHITE,
             );
 
+            // Draw menu button
+            draw_rectangle(
+                screen_width() / 2. - 100.,
+                screen_height() / 2. + 60.,
+                200.,
+                50.,
+                DARKGRAY,
+            );
+            draw_text(
+                "Menu",
+                screen_width() / 2. - 35.,
+                screen_height() / 2. + 90.,
+                30.,
+                WHITE,
+            );
+
             next_frame().await;
             continue;
         }
 
-        let mouse_position: Vec2 = mouse_position().into();
-        let target = rope.particles[0].position
-            + (mouse_position - rope.particles[0].position) * LERP_FACTOR;
-
-        for _ in 0..SUBSTEPS {
-            rope.update(target);
-            check_collisions(
-                &mut rope,
-                &mut enemies,
-                &mut points,
-                &mut score,
-                &mut game_over,
-            );
-            check_enemy_collisions(&mut enemies);
+        if is_key_pressed(KeyCode::GraveAccent) {
+            console.open = !console.open;
         }
-
-        if get_time() - last_spawn_time >= ENEMY_SPAWN_INTERVAL as f64 {
-            enemies.push(Enemy::new(frame));
-            last_spawn_time = get_time();
+        if console.open {
+            let before = runner.view(|game| game.config);
+            let mut config = before;
+            console.update(&mut config);
+            if config != before {
+                runner.edit(Box::new(move |game| game.config = config));
+            }
+        } else {
+            handle_keys(&mut runner);
+            if is_key_pressed(KeyCode::N) {
+                bloom_enabled = !bloom_enabled;
+            }
+            if is_key_pressed(KeyCode::Tab) {
+                show_leaderboard = !show_leaderboard;
+            }
+            if is_key_pressed(KeyCode::Key2) {
+                smoother.set_filter(smoother.filter.next());
+            }
+            // Cycles open edges, walls and a wrapping arena.
+            if is_key_pressed(KeyCode::W) {
+                match (frame.topology, frame.left) {
+                    (ArenaTopology::Bounded, EdgeKind::Open) => frame.set_edges(EdgeKind::Wall),
+                    (ArenaTopology::Bounded, EdgeKind::Wall) => {
+                        frame.topology = ArenaTopology::Wrapping;
+                    }
+                    (ArenaTopology::Wrapping, _) => {
+                        frame.topology = ArenaTopology::Bounded;
+                        frame.set_edges(EdgeKind::Open);
+                    }
+                }
+                runner.edit(Box::new(move |game| game.frame = frame));
+            }
+            if is_key_pressed(KeyCode::F) {
+                frame.fit_to_window = !frame.fit_to_window;
+                frame.update();
+                runner.edit(Box::new(move |game| {
+                    game.frame = frame;
+                    game.clamp_to_frame();
+                }));
+            }
+            // Letterboxing only applies to a fitted arena, so turning it on
+            // fits the arena too.
+            if is_key_pressed(KeyCode::Q) {
+                frame.letterbox = !frame.letterbox;
+                frame.fit_to_window |= frame.letterbox;
+                frame.update();
+                runner.edit(Box::new(move |game| {
+                    game.frame = frame;
+                    game.clamp_to_frame();
+                }));
+            }
         }
 
-        if get_time() - last_point_spawn_time >= POINT_SPAWN_INTERVAL as f64
-            && points.len() < MAX_POINTS
-        {
-            points.push(Point::new(frame));
-            last_point_spawn_time = get_time();
+        let target = frame.screen_to_world(pointer_position());
+        let cursor = frame.clamp(smoother.apply(target, get_frame_time()));
+        if !console.open && is_mouse_button_pressed(MouseButton::Left) {
+            runner.edit(Box::new(move |game| game.flick(cursor)));
         }
-
-        for enemy in &mut enemies {
-            enemy.update(rope.particles[0].position, frame);
+        if !console.open && is_key_down(KeyCode::R) {
+            runner.edit(Box::new(|game| {
+                game.rewind();
+            }));
+        } else {
+            let dt = runner.view(|game| game.config.frame_dt(get_frame_time()));
+            runner.step(cursor, dt);
         }
-
-        for enemy in &mut enemies {
-            enemy.particle.update();
+        let recovery = runner.view(|game| game.rope.last_recovery);
+        if recovery != reported_recovery {
+            if let Some(recovery) = recovery {
+                let warning = format!(
+                    "{} rope particle(s) went non-finite on update {}, restored",
+                    recovery.particles, recovery.update
+                );
+                eprintln!("warning: {}", warning);
+                console.print(warning);
+            }
+            reported_recovery = recovery;
+        }
+        runner.edit(Box::new(|game| {
+            if game.game_over {
+                game.finish_run();
+            }
+        }));
+
+        let mut bloom = bloom.as_mut().filter(|_| bloom_enabled);
+        match &mut bloom {
+            Some(bloom) => bloom.begin(frame.camera()),
+            None => {
+                if let Some(camera) = frame.camera() {
+                    set_camera(&camera);
+                }
+            }
         }
 
-        points.retain(|point| point.active);
-        enemies.retain(|enemy| enemy.active);
+        runner.view(|game| {
+            game.draw(&sprites);
 
-        rope.draw();
+            // The HUD goes on after bloom so text stays sharp.
+            match &bloom {
+                Some(bloom) => bloom.end(),
+                None => set_default_camera(),
+            }
+            frame.draw_letterbox();
 
-        for enemy in &enemies {
-            enemy.draw();
-        }
+            if game.danger_vignette {
+                draw_danger_vignette(game.danger());
+            }
+            if let Some(banner) = &game.banner {
+                banner.draw();
+            }
 
-        for point in &points {
-            point.draw();
+            draw_text(&format!("Score: {}", game.score), 20.0, 20.0, 30.0, WHITE);
+            draw_health_bar(game.health, game.max_health);
+            draw_text(&format!("Streak: {}", game.streak), 20.0, 70.0, 24.0, WHITE);
+            draw_text(
+                &format!("Enemies: {}", game.active_enemy_count()),
+                20.0,
+                95.0,
+                24.0,
+                WHITE,
+            );
+            draw_threat_bar(game.threat_level());
+        });
+        if show_leaderboard {
+            runner.view(|game| draw_leaderboard(&game.leaderboard));
+        }
+        fps_counter.draw();
+        console.draw();
+
+        // Last, so the capture has everything drawn this frame.
+        if is_key_pressed(KeyCode::F12) {
+            match save_screenshot() {
+                Ok(path) => console.print(format!("saved {}", path)),
+                Err(err) => {
+                    eprintln!("warning: {}", err);
+                    console.print(err);
+                }
+            }
         }
 
-        draw_text(&format!("Score: {}", score), 20.0, 20.0, 30.0, WHITE);
-
-        draw_rectangle_lines(
-            (screen_width() - frame.width) / 2.,
-            (screen_height() - frame.height) / 2.,
-            frame.width,
-            frame.height,
-            BORDER_THICKNESS,
-            BORDER_COLOR,
-        );
+        if frame.update() {
+            runner.edit(Box::new(move |game| {
+                game.frame = frame;
+                game.clamp_to_frame();
+            }));
+        }
 
-        if score % 5 == 0 && score != last_extended_score {
-            rope.extend();
-            last_extended_score = score;
-            if rope.constraint_strength < 1.5 {
-                rope.constraint_strength += 0.1;
-            }
+        // The browser paces frames itself and can't block the main thread.
+        #[cfg(not(target_arch = "wasm32"))]
+        if let Some(delay) = frame_cap_delay(
+            runner.view(|game| game.config.target_fps),
+            get_time() - frame_start,
+        ) {
+            std::thread::sleep(delay);
         }
-        frame.update();
 
         next_frame().await;
     }
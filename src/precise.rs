@@ -0,0 +1,97 @@
+//! Double-precision copy of the rope core: Verlet integration and
+//! Gauss-Seidel distance constraints only, with positions kept as `DVec2`
+//! and converted to `Vec2` for drawing. Meant for measuring how much of a
+//! long rope's drift comes from `f32` rounding, not for gameplay, so pins,
+//! collisions and the other `Rope` options are left out.
+
+use crate::{Rope, CONSTRAINT_ITERATIONS, SUBSTEPS, TIME_STEP};
+use macroquad::math::DVec2;
+use macroquad::prelude::*;
+
+#[derive(Clone, Copy, Debug)]
+pub struct PreciseParticle {
+    pub position: DVec2,
+    pub old_position: DVec2,
+    pub acceleration: DVec2,
+    pub friction: f64,
+}
+
+impl PreciseParticle {
+    pub fn update(&mut self) {
+        let velocity = (self.position - self.old_position) * self.friction;
+        let time_step = TIME_STEP as f64;
+        self.old_position = self.position;
+        self.position += velocity + self.acceleration * time_step * time_step;
+        self.acceleration = DVec2::ZERO;
+    }
+}
+
+/// An open rope hanging from particle 0, which follows the update target.
+#[derive(Clone, Debug)]
+pub struct PreciseRope {
+    pub particles: Vec<PreciseParticle>,
+    pub constraint_strength: f64,
+    pub segment_length: f64,
+    pub gravity: f64,
+}
+
+impl PreciseRope {
+    /// Widens `rope`'s particles and settings. Options this path doesn't
+    /// model are dropped.
+    pub fn from_rope(rope: &Rope) -> Self {
+        Self {
+            particles: rope
+                .particles
+                .iter()
+                .map(|particle| PreciseParticle {
+                    position: particle.position.as_dvec2(),
+                    old_position: particle.old_position.as_dvec2(),
+                    acceleration: particle.acceleration.as_dvec2(),
+                    friction: particle.friction as f64,
+                })
+                .collect(),
+            constraint_strength: rope.constraint_strength as f64,
+            segment_length: rope.segment_length as f64,
+            gravity: rope.gravity as f64,
+        }
+    }
+
+    /// Same order of operations as `Rope::update` with Verlet and
+    /// Gauss-Seidel: move the anchor, relax the constraints, integrate.
+    pub fn update(&mut self, target: Vec2) {
+        self.particles[0].position = target.as_dvec2();
+        let strength = self.constraint_strength / SUBSTEPS as f64;
+        for _ in 0..CONSTRAINT_ITERATIONS {
+            for i in 0..self.particles.len() - 1 {
+                let delta = self.particles[i + 1].position - self.particles[i].position;
+                let length = delta.length();
+                let offset = delta * (length - self.segment_length) / length * strength;
+                if i > 0 {
+                    self.particles[i].position += offset;
+                }
+                self.particles[i + 1].position -= offset;
+            }
+        }
+        for particle in self.particles.iter_mut().skip(1) {
+            particle.acceleration.y += self.gravity;
+            particle.update();
+        }
+    }
+
+    /// Particle positions rounded to `f32`, for drawing or for comparing
+    /// against a `Rope`.
+    pub fn positions(&self) -> Vec<Vec2> {
+        self.particles
+            .iter()
+            .map(|particle| particle.position.as_vec2())
+            .collect()
+    }
+
+    /// Largest difference between a segment's length and its rest length.
+    pub fn max_segment_error(&self) -> f64 {
+        self.particles
+            .windows(2)
+            .map(|pair| (pair[1].position.distance(pair[0].position) - self.segment_length).abs())
+            .fold(0.0, f64::max)
+    }
+}
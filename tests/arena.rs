@@ -0,0 +1,124 @@
+//! Arena geometry: spawning, walls and wrapping.
+
+use macroquad::prelude::*;
+use sketch::*;
+
+fn arena() -> Frame {
+    Frame::with_size(ARENA_WIDTH, ARENA_HEIGHT)
+}
+
+#[test]
+fn spawns_cluster_away_from_the_anchor() {
+    let frame = arena();
+    let anchor = vec2(10.0, ARENA_HEIGHT / 2.0);
+    let mut rng = Rng::new(11);
+    let samples = 2000;
+    let (mut weighted, mut uniform) = (0, 0);
+    for _ in 0..samples {
+        weighted += (frame.weighted_edge_point(anchor, &mut rng).x > ARENA_WIDTH / 2.0) as usize;
+        uniform += (frame.random_edge_point(&mut rng).x > ARENA_WIDTH / 2.0) as usize;
+    }
+    assert!(
+        weighted > uniform + samples / 10,
+        "{} weighted and {} uniform spawns on the far half",
+        weighted,
+        uniform
+    );
+}
+
+#[test]
+fn edge_viscosity_slows_particles_near_the_walls() {
+    let frame = arena();
+    let launch = |x: f32| {
+        let mut particle = Particle::new(vec2(x, ARENA_HEIGHT / 2.0));
+        particle.apply_impulse(vec2(5.0, 0.0));
+        for _ in 0..8 {
+            particle.update();
+            apply_edge_viscosity(&mut particle, frame, 0.5, EDGE_MARGIN);
+        }
+        (particle.position - particle.old_position).length()
+    };
+    let at_wall = launch(ARENA_WIDTH - EDGE_MARGIN);
+    let in_middle = launch(ARENA_WIDTH / 2.0);
+    assert!(
+        at_wall < in_middle * 0.5,
+        "{} at the wall, {} in the middle",
+        at_wall,
+        in_middle
+    );
+}
+
+#[test]
+fn enemies_leave_by_open_edges_and_stop_at_walls() {
+    let mut frame = arena();
+    frame.left = EdgeKind::Wall;
+    frame.right = EdgeKind::Open;
+    let middle = ARENA_HEIGHT / 2.0;
+    let run = |start: Vec2, target: Vec2| {
+        let mut enemy = Enemy::at(start, target, EnemyKind::Chaser);
+        for _ in 0..300 {
+            enemy.update(target, frame, ENEMY_SPEED, ENEMY_TURN_RATE, f32::INFINITY);
+        }
+        enemy
+    };
+    let leaving = run(
+        vec2(ARENA_WIDTH - 30.0, middle),
+        vec2(ARENA_WIDTH + 200.0, middle),
+    );
+    assert!(!leaving.active);
+    let walled = run(vec2(30.0, middle), vec2(-200.0, middle));
+    assert!(walled.active);
+    assert_eq!(walled.particle.position.x, 0.0);
+    // The rope's anchor is held in at either kind of edge.
+    assert_eq!(frame.clamp(vec2(ARENA_WIDTH + 50.0, middle)).x, ARENA_WIDTH);
+
+    // A whole game step integrates enemies once more after steering them,
+    // which mustn't carry one through the wall it was stopped at.
+    let mut game = Game::with_seed(frame, 7);
+    game.set_spawning(false, true);
+    game.config.enemy_turn_rate = 0.0;
+    let start = vec2(30.0, ARENA_HEIGHT - 50.0);
+    game.enemies.push(Enemy::at(
+        start,
+        start - vec2(100.0, 0.0),
+        EnemyKind::Chaser,
+    ));
+    let cursor = vec2(ARENA_WIDTH - 100.0, 100.0);
+    for _ in 0..120 {
+        game.step(cursor, TIME_STEP);
+        let enemy = &game.enemies[0];
+        assert!(
+            enemy.particle.position.x >= 0.0,
+            "{}",
+            enemy.particle.position
+        );
+    }
+    assert_eq!(game.enemies[0].particle.position.x, 0.0);
+}
+
+#[test]
+fn enemies_wrap_from_the_right_edge_to_the_left() {
+    let mut frame = arena();
+    frame.topology = ArenaTopology::Wrapping;
+    let middle = ARENA_HEIGHT / 2.0;
+    // The short way to a target just inside the left edge is across the seam.
+    let target = vec2(30.0, middle);
+    let mut enemy = Enemy::at(vec2(ARENA_WIDTH - 20.0, middle), target, EnemyKind::Chaser);
+    let mut wrapped = false;
+    for _ in 0..120 {
+        let before = enemy.particle.position.x;
+        enemy.update(target, frame, ENEMY_SPEED, ENEMY_TURN_RATE, f32::INFINITY);
+        let x = enemy.particle.position.x;
+        assert!(enemy.active);
+        // The jump across the seam isn't read as velocity.
+        let velocity = enemy.particle.position - enemy.particle.old_position;
+        assert!(velocity.length() < 10.0);
+        assert!(
+            !(100.0..ARENA_WIDTH - 100.0).contains(&x),
+            "cut through at {}",
+            x
+        );
+        wrapped |= before > ARENA_WIDTH / 2.0 && x < ARENA_WIDTH / 2.0;
+    }
+    assert!(wrapped);
+}
@@ -0,0 +1,258 @@
+//! The rope, enemy, point and projectile collision helpers.
+
+use macroquad::prelude::*;
+use sketch::*;
+
+fn arena() -> Frame {
+    Frame::with_size(ARENA_WIDTH, ARENA_HEIGHT)
+}
+
+/// An ordinary point moved to `position`.
+fn point_at(position: Vec2) -> Point {
+    let mut point = Point::new(arena(), &mut Rng::new(0));
+    point.position = position;
+    point
+}
+
+#[test]
+fn shield_rope_never_scores() {
+    let mut shield = Rope::new(vec2(300.0, 200.0), 10);
+    shield.role = RopeRole::Shield;
+    let mut points: Vec<Point> = shield
+        .particles
+        .iter()
+        .map(|particle| point_at(particle.position))
+        .collect();
+    let mut score = 0;
+    let mut heatmap = CollisionHeatmap::new(arena());
+    for frame_index in 0..10 {
+        check_collisions(
+            &mut shield,
+            &mut [],
+            &mut points,
+            &mut score,
+            &mut heatmap,
+            frame_index,
+        );
+    }
+    assert_eq!(score, 0);
+    assert!(points.iter().all(|point| point.active));
+
+    shield.role = RopeRole::Main;
+    check_collisions(
+        &mut shield,
+        &mut [],
+        &mut points,
+        &mut score,
+        &mut heatmap,
+        10,
+    );
+    assert_eq!(score, points.len() as i32);
+}
+
+#[test]
+fn projectile_hits_the_rope() {
+    let rope = Rope::new(vec2(300.0, 200.0), 10);
+    let body = rope.particles[4].position;
+    let mut projectile = Projectile::new(body + vec2(0.0, -20.0), body);
+    assert_eq!(
+        rope_contact(&rope, projectile.particle.position, projectile.radius),
+        None
+    );
+    let mut hit = None;
+    for _ in 0..10 {
+        projectile.update(arena());
+        hit = hit.or(rope_contact(
+            &rope,
+            projectile.particle.position,
+            projectile.radius,
+        ));
+    }
+    assert!(
+        hit.is_some_and(|index| index.abs_diff(4) <= 1),
+        "hit {:?}",
+        hit
+    );
+}
+
+#[test]
+fn enemy_pairs_carry_over_between_frames() {
+    let anchor = vec2(400.0, 300.0);
+    let mut enemies: Vec<Enemy> = (0..100)
+        .map(|i| Enemy::at(vec2(i as f32 * 5.0, 300.0), anchor, EnemyKind::Chaser))
+        .collect();
+    let pairs = 100 * 99 / 2;
+    let mut cursor = (0, 1);
+    let mut checked = 0;
+    let mut frames = 0;
+    while checked < pairs {
+        let work = check_enemy_collisions_budgeted(&mut enemies, 1000, &mut cursor);
+        assert!(work <= 1000);
+        checked += work;
+        frames += 1;
+    }
+    assert_eq!(frames, 5);
+    assert_eq!(checked, 5000);
+}
+
+#[test]
+fn weighted_push_steadies_the_rope_near_the_anchor() {
+    // How far the first segment gets yanked off its rest length.
+    let worst_strain = |flat: bool| {
+        let anchor = vec2(300.0, 200.0);
+        let mut rope = Rope::new(anchor, 10);
+        let mut heatmap = CollisionHeatmap::new(arena());
+        let share = if flat { 0.5 } else { rope.push_share(1) };
+        let mut worst: f32 = 0.0;
+        for _ in 0..60 {
+            rope.update(anchor);
+            // An enemy keeps pressing into the particle next to the anchor.
+            let press = rope.particles[1].position + vec2(0.0, 12.0);
+            let mut enemies = [Enemy::at(press, anchor, EnemyKind::Chaser)];
+            let radius = rope.collision_radius(1);
+            check_enemy_collisions_with_particle(
+                &mut rope.particles[1],
+                radius,
+                share,
+                &mut enemies,
+                &mut heatmap,
+                None,
+            );
+            worst = worst.max(rope.strain(0).abs());
+        }
+        worst
+    };
+    let weighted = worst_strain(false);
+    let flat = worst_strain(true);
+    assert!(
+        weighted < flat * 0.8,
+        "{} weighted against {} flat",
+        weighted,
+        flat
+    );
+}
+
+#[test]
+fn magnetic_tip_collects_at_range() {
+    let reach = |tip_pickup_radius: f32| {
+        let mut rope = Rope::new(vec2(300.0, 200.0), 10);
+        rope.tip_pickup_radius = tip_pickup_radius;
+        let tip = rope.particles[9].position;
+        let mut points = [point_at(tip + vec2(30.0, 0.0))];
+        let mut score = 0;
+        let mut heatmap = CollisionHeatmap::new(arena());
+        check_collisions(&mut rope, &mut [], &mut points, &mut score, &mut heatmap, 0);
+        score
+    };
+    assert_eq!(reach(0.0), 0);
+    assert_eq!(reach(40.0), 1);
+}
+
+#[test]
+fn swept_particle_collects_a_point_it_passes() {
+    let mut particle = Particle::new(vec2(100.0, 100.0));
+    particle.position = vec2(200.0, 100.0);
+    let mut points = [point_at(vec2(150.0, 105.0))];
+    let mut score = 0;
+    check_point_collisions_with_particle(&mut particle, &mut points, &mut score);
+    assert_eq!(score, 1);
+    assert!(!points[0].active);
+}
+
+#[test]
+fn resting_anchor_leaves_distant_points_alone() {
+    let mut game = Game::with_seed(arena(), 7);
+    game.set_spawning(false, true);
+    let anchor = vec2(600.0, 500.0);
+    for _ in 0..200 {
+        game.step(anchor, TIME_STEP);
+    }
+    assert!(game.rope.particles[0].position.distance(anchor) < 1.0);
+    game.points.push(point_at(vec2(150.0, 200.0)));
+    for _ in 0..60 {
+        game.step(anchor, TIME_STEP);
+    }
+    assert_eq!(game.score, 0);
+    assert_eq!(game.points.len(), 1);
+}
+
+#[test]
+fn thick_base_blocks_what_the_thin_tip_lets_by() {
+    let mut rope = Rope::new(vec2(300.0, 200.0), 10);
+    rope.base_collision_radius = 20.0;
+    rope.tip_collision_radius = 3.0;
+    let clearance = ENEMY_RADIUS + 10.0;
+    let near = |i: usize| rope.particles[i].position + vec2(0.0, clearance);
+    let (base, tip) = (near(1), near(9));
+    let mut enemies = [
+        Enemy::at(base, base, EnemyKind::Chaser),
+        Enemy::at(tip, tip, EnemyKind::Chaser),
+    ];
+    let mut heatmap = CollisionHeatmap::new(arena());
+    let contacts = check_collisions(&mut rope, &mut enemies, &mut [], &mut 0, &mut heatmap, 0);
+    assert_eq!(contacts, 1);
+    assert!(enemies[0].particle.position.y > base.y + 1.0);
+    assert_eq!(enemies[1].particle.position, tip);
+}
+
+#[test]
+fn a_point_scores_once_across_every_substep() {
+    let mut rope = Rope::new(vec2(300.0, 200.0), 10);
+    // Within reach of three particles, on each of the substeps.
+    let mut points = [point_at(rope.particles[4].position)];
+    let mut score = 0;
+    let mut heatmap = CollisionHeatmap::new(arena());
+    check_collisions(&mut rope, &mut [], &mut points, &mut score, &mut heatmap, 0);
+    assert_eq!(score, 1);
+    assert!(!points[0].active);
+
+    // The same through a whole game step of `SUBSTEPS` rope updates.
+    let mut game = Game::with_seed(arena(), 7);
+    game.set_spawning(false, true);
+    game.points.clear();
+    game.points.push(point_at(game.rope.particles[0].position));
+    let anchor = game.rope.particles[0].position;
+    game.step(anchor, TIME_STEP);
+    assert_eq!(game.score, 1);
+}
+
+#[test]
+fn overlap_splits_by_the_push_fraction() {
+    // How far the rope particle and the enemy each move out of a 6 px
+    // overlap at particle `index`.
+    let split = |fraction: f32, index: usize| {
+        let mut rope = Rope::new(vec2(300.0, 200.0), 10);
+        rope.rope_push_fraction = fraction;
+        let radius = rope.collision_radius(index);
+        let start = rope.particles[index].position;
+        let enemy_start = start + vec2(0.0, radius + ENEMY_RADIUS - 6.0);
+        let mut enemies = [Enemy::at(enemy_start, start, EnemyKind::Chaser)];
+        let share = rope.push_share(index);
+        check_enemy_collisions_with_particle(
+            &mut rope.particles[index],
+            radius,
+            share,
+            &mut enemies,
+            &mut CollisionHeatmap::new(arena()),
+            None,
+        );
+        (
+            rope.particles[index].position.distance(start),
+            enemies[0].particle.position.distance(enemy_start),
+        )
+    };
+    for fraction in [0.0, 0.25, 0.5, 1.0] {
+        let (rope, enemy) = split(fraction, 5);
+        assert!(
+            (rope - 6.0 * fraction).abs() < 1e-4,
+            "{} at {}",
+            rope,
+            fraction
+        );
+        assert!((enemy - 6.0 * (1.0 - fraction)).abs() < 1e-4);
+    }
+    // The pinned anchor never gives way.
+    let (rope, enemy) = split(0.75, 0);
+    assert_eq!(rope, 0.0);
+    assert!((enemy - 6.0).abs() < 1e-4);
+}
@@ -0,0 +1,73 @@
+//! Enemy steering and behaviour, one enemy at a time.
+
+use macroquad::prelude::*;
+use sketch::*;
+
+fn arena() -> Frame {
+    Frame::with_size(ARENA_WIDTH, ARENA_HEIGHT)
+}
+
+#[test]
+fn homing_cannot_reverse_instantly() {
+    let start = vec2(400.0, 300.0);
+    let (right, left) = (vec2(600.0, 300.0), vec2(200.0, 300.0));
+    let chase = |turn_rate: f32| {
+        let mut enemy = Enemy::at(start, right, EnemyKind::Chaser);
+        for _ in 0..10 {
+            enemy.update(right, arena(), ENEMY_SPEED, turn_rate, f32::INFINITY);
+        }
+        enemy.update(left, arena(), ENEMY_SPEED, turn_rate, f32::INFINITY);
+        enemy.heading
+    };
+    let heading = chase(ENEMY_TURN_RATE);
+    assert!(heading.x > 0.0, "turned to {}", heading);
+    assert!(heading.angle_between(Vec2::X).abs() <= ENEMY_TURN_RATE * TIME_STEP + 1e-4);
+    assert!(chase(1000.0).x < 0.0);
+}
+
+#[test]
+fn enemy_idles_until_the_anchor_is_in_range() {
+    let radius = 150.0;
+    let mut enemy = Enemy::at(vec2(600.0, 300.0), vec2(200.0, 300.0), EnemyKind::Chaser);
+    let mut target = vec2(200.0, 300.0);
+    for _ in 0..60 {
+        enemy.update(target, arena(), ENEMY_SPEED, ENEMY_TURN_RATE, radius);
+        assert_eq!(enemy.state, EnemyState::Idle);
+        assert!(
+            enemy
+                .particle
+                .position
+                .distance(enemy.particle.old_position)
+                < ENEMY_SPEED * 0.5
+        );
+    }
+    target = enemy.particle.position - vec2(radius - 10.0, 0.0);
+    enemy.update(target, arena(), ENEMY_SPEED, ENEMY_TURN_RATE, radius);
+    assert_eq!(enemy.state, EnemyState::Hunting);
+}
+
+#[test]
+fn orbiter_circles_then_closes_in() {
+    let target = vec2(400.0, 300.0);
+    let start = target + vec2(140.0, 0.0);
+    let mut enemy = Enemy::at(start, target, EnemyKind::Orbiter);
+    let step = |enemy: &mut Enemy| {
+        enemy.update(target, arena(), ENEMY_SPEED, ENEMY_TURN_RATE, f32::INFINITY);
+        enemy.particle.position.distance(target)
+    };
+    // The first second goes round the target, not towards it.
+    for _ in 0..60 {
+        let distance = step(&mut enemy);
+        assert!((120.0..=150.0).contains(&distance), "at {}", distance);
+    }
+    assert!(enemy.particle.position.distance(start) > 100.0);
+    // Then it spirals in, and once the orbit time is up it homes.
+    let spiral = ((ORBITER_ORBIT_TIME - 1.0) / TIME_STEP) as usize;
+    let mut distance = 0.0;
+    for _ in 0..spiral {
+        distance = step(&mut enemy);
+    }
+    assert!(distance < ORBITER_RADIUS - 40.0, "at {}", distance);
+    let closest = (0..180).map(|_| step(&mut enemy)).fold(f32::MAX, f32::min);
+    assert!(closest < 30.0, "closest {}", closest);
+}
@@ -0,0 +1,934 @@
+//! Headless runs of `Game::step`.
+
+use macroquad::prelude::*;
+use sketch::*;
+
+/// A quiet game in the default arena: nothing spawns unless a test asks.
+fn game() -> Game {
+    let mut game = Game::with_seed(Frame::with_size(ARENA_WIDTH, ARENA_HEIGHT), 7);
+    game.set_spawning(false, true);
+    game
+}
+
+/// Steps `game` for `steps` frames with the cursor held on the anchor.
+fn hold(game: &mut Game, steps: usize) {
+    for _ in 0..steps {
+        let anchor = game.rope.particles[0].position;
+        game.step(anchor, TIME_STEP);
+    }
+}
+
+/// Steps `game` once by `dt` with the cursor held on the anchor.
+fn hold_for(game: &mut Game, dt: f32) {
+    let anchor = game.rope.particles[0].position;
+    game.step(anchor, dt);
+}
+
+#[test]
+fn sustained_contact_drains_health_to_zero() {
+    let mut health = MAX_HEALTH;
+    let mut seconds = 0.0;
+    while health > 0.0 {
+        update_health(&mut health, MAX_HEALTH, 1, TIME_STEP);
+        seconds += TIME_STEP;
+        assert!(seconds < 10.0, "health stuck at {}", health);
+    }
+    update_health(&mut health, MAX_HEALTH, 1, TIME_STEP);
+    assert_eq!(health, 0.0);
+
+    let mut game = game();
+    game.health = 0.5;
+    let body = game.rope.particles[5].position;
+    let anchor = game.rope.particles[0].position;
+    game.enemies
+        .push(Enemy::at(body + vec2(0.0, 5.0), anchor, EnemyKind::Chaser));
+    hold(&mut game, 1);
+    assert_eq!(game.health, 0.0);
+    assert!(game.game_over);
+}
+
+#[test]
+fn shooter_fires_on_schedule() {
+    let mut game = game();
+    game.config.enemy_speed = 0.0;
+    game.config.detection_radius = f32::INFINITY;
+    let anchor = game.rope.particles[0].position;
+    game.enemies.push(Enemy::at(
+        anchor + vec2(200.0, 0.0),
+        anchor,
+        EnemyKind::Shooter,
+    ));
+
+    let mut shots: Vec<usize> = Vec::new();
+    for step in 0..500 {
+        let before = game.projectiles.len();
+        hold(&mut game, 1);
+        if game.projectiles.len() > before {
+            shots.push(step);
+        }
+    }
+    let interval = (SHOOTER_FIRE_INTERVAL / TIME_STEP).round() as usize;
+    assert_eq!(shots.len(), 3, "fired on steps {:?}", shots);
+    for pair in shots.windows(2) {
+        assert!(
+            (pair[1] - pair[0]).abs_diff(interval) <= 1,
+            "fired on steps {:?}",
+            shots
+        );
+    }
+}
+
+#[test]
+fn eased_anchor_moves_farther_for_a_far_cursor() {
+    for easing in [AnchorEasing::EaseOut, AnchorEasing::EaseInOut] {
+        assert!(easing.fraction(EASING_DISTANCE) > easing.fraction(EASING_DISTANCE / 10.0));
+
+        let moved = |offset: f32| {
+            let mut game = game();
+            game.anchor_easing = easing;
+            let anchor = game.rope.particles[0].position;
+            game.step(anchor + vec2(offset, 0.0), TIME_STEP);
+            (game.rope.particles[0].position.x - anchor.x) / offset
+        };
+        assert!(
+            moved(EASING_DISTANCE) > moved(10.0),
+            "{:?} covers no more of a far gap",
+            easing
+        );
+    }
+}
+
+#[test]
+fn whip_crack_runs_the_rope_and_kills_in_its_path() {
+    let mut game = game();
+    game.config.enemy_speed = 0.0;
+    game.rope.translate(vec2(200.0, 200.0));
+    let (near, far) = (
+        game.rope.particles[1].position,
+        game.rope.particles[8].position,
+    );
+    let anchor = game.rope.particles[0].position;
+    for position in [
+        near + vec2(0.0, 22.0),
+        far + vec2(0.0, 22.0),
+        far + vec2(0.0, 100.0),
+    ] {
+        game.enemies
+            .push(Enemy::at(position, anchor, EnemyKind::Chaser));
+    }
+    hold(&mut game, 5);
+    assert_eq!(game.enemies.len(), 3);
+
+    game.crack_whip();
+    let segments = game.rope.particles.len() - 1;
+    let mut visited = vec![false; segments];
+    while let Some(segment) = game.whip_segment() {
+        visited[segment] = true;
+        hold(&mut game, 1);
+    }
+    assert!(visited.iter().all(|&seen| seen), "visited {:?}", visited);
+    assert_eq!(game.destroyed, 2);
+    assert_eq!(game.enemies.len(), 1);
+    assert_eq!(game.enemies[0].particle.position, far + vec2(0.0, 100.0));
+}
+
+#[test]
+fn shrinking_the_arena_keeps_everything_inside() {
+    let mut game = game();
+    game.rope.translate(vec2(650.0, 450.0));
+    let anchor = game.rope.particles[0].position;
+    game.enemies
+        .push(Enemy::at(vec2(780.0, 580.0), anchor, EnemyKind::Chaser));
+    game.points.push(Point::new(game.frame, &mut Rng::new(3)));
+    game.points[0].position = vec2(790.0, 590.0);
+    hold(&mut game, 10);
+
+    game.frame = Frame::with_size(400.0, 300.0);
+    game.clamp_to_frame();
+    let frame = game.frame;
+    assert!(game
+        .rope
+        .particles
+        .iter()
+        .chain(game.enemies.iter().map(|enemy| &enemy.particle))
+        .all(|particle| is_in_frame(particle, frame)));
+    assert!(game
+        .points
+        .iter()
+        .all(|point| frame.clamp(point.position) == point.position));
+    // Clamping zeroes the rope's velocity, so the jump doesn't fling it.
+    assert!(game.rope.kinetic_energy() == 0.0);
+}
+
+#[test]
+fn repel_mode_points_run_from_the_rope() {
+    let approach = |repel: bool| {
+        let mut game = game();
+        game.point_repel = repel;
+        game.rope.translate(vec2(200.0, 200.0));
+        let anchor = game.rope.particles[0].position;
+        let start = game.rope.particles[9].position + vec2(100.0, 0.0);
+        game.points.push(Point::new(game.frame, &mut Rng::new(3)));
+        game.points[0].position = start;
+        for step in 0..30 {
+            game.step(anchor + vec2(step as f32 * 2.0, 0.0), TIME_STEP);
+        }
+        game.points[0].position.x - start.x
+    };
+    assert!(approach(true) > 20.0);
+    assert_eq!(approach(false), 0.0);
+}
+
+#[test]
+fn spawn_backlog_drains_within_the_budget() {
+    let mut game = game();
+    game.spawning_enabled = true;
+    game.config.enemy_speed = 0.0;
+    game.config.enemy_spawn_interval = f32::INFINITY;
+    game.config.point_spawn_interval = f32::INFINITY;
+    game.pending_enemy_spawns = 25;
+    let mut frames = 0;
+    while game.pending_enemy_spawns > 0 {
+        let before = game.enemies.len();
+        hold(&mut game, 1);
+        assert!(game.enemies.len() - before <= game.spawn_budget);
+        frames += 1;
+    }
+    assert_eq!(game.enemies.len(), 25);
+    assert_eq!(frames, 25_usize.div_ceil(game.spawn_budget));
+}
+
+#[test]
+fn an_escape_resets_the_streak() {
+    let mut game = game();
+    game.rope.translate(vec2(200.0, 200.0));
+    let anchor = game.rope.particles[0].position;
+    for i in [3, 6] {
+        let position = game.rope.particles[i].position + vec2(0.0, 22.0);
+        game.enemies
+            .push(Enemy::at(position, anchor, EnemyKind::Chaser));
+    }
+    game.config.enemy_speed = 0.0;
+    game.crack_whip();
+    while game.whip_wave.is_some() {
+        hold(&mut game, 1);
+    }
+    assert_eq!(game.streak, 2);
+    let score = game.score;
+    assert!(score > 0);
+
+    game.config.enemy_speed = ENEMY_SPEED;
+    game.config.detection_radius = f32::INFINITY;
+    game.enemies.push(Enemy::at(
+        anchor - vec2(60.0, 0.0),
+        anchor,
+        EnemyKind::Chaser,
+    ));
+    for _ in 0..600 {
+        if game.escaped > 0 {
+            break;
+        }
+        hold(&mut game, 1);
+    }
+    assert_eq!(game.escaped, 1);
+    assert_eq!(game.streak, 0);
+    assert_eq!(game.score, score - ESCAPE_PENALTY);
+}
+
+#[test]
+fn enemies_eat_points_without_scoring() {
+    let mut game = game();
+    game.config.enemy_speed = 0.0;
+    let (position, anchor) = (vec2(500.0, 400.0), game.rope.particles[0].position);
+    game.enemies
+        .push(Enemy::at(position, anchor, EnemyKind::Chaser));
+    game.points.push(Point::new(game.frame, &mut Rng::new(3)));
+    game.points[0].position = position + vec2(5.0, 0.0);
+    hold(&mut game, 1);
+    assert!(game.points.is_empty());
+    assert_eq!(game.score, 0);
+    assert!(game.floating_texts.is_empty());
+    assert_eq!(game.enemies.len(), 1);
+}
+
+#[test]
+fn grazing_projectiles_bounce_and_square_ones_hit() {
+    let fire = |direction: Vec2| {
+        let mut game = game();
+        game.rope.translate(vec2(200.0, 200.0));
+        let body = game.rope.particles[5].position;
+        let start = body - direction * 50.0;
+        game.projectiles
+            .push(Projectile::new(start, start + direction));
+        hold(&mut game, 30);
+        game
+    };
+
+    let grazed = fire(vec2(1.0, 0.2).normalize());
+    assert_eq!(grazed.health, MAX_HEALTH);
+    assert_eq!(grazed.projectiles.len(), 1);
+    assert!(grazed.projectiles[0].velocity().y < 0.0);
+
+    let struck = fire(Vec2::Y);
+    // Less what has regenerated since.
+    assert!(struck.health <= MAX_HEALTH - PROJECTILE_DAMAGE + HEALTH_REGEN_RATE * 0.5);
+    assert!(struck.projectiles.is_empty());
+}
+
+/// Cursor path for scripted runs: a slow loop around the arena centre.
+fn scripted_cursor(step: usize) -> Vec2 {
+    let angle = step as f32 * 0.02;
+    vec2(ARENA_WIDTH / 2.0, ARENA_HEIGHT / 2.0) + Vec2::from_angle(angle) * 200.0
+}
+
+#[test]
+fn seeded_games_replay_identically() {
+    let frame = Frame::with_size(ARENA_WIDTH, ARENA_HEIGHT);
+    let (mut a, mut b) = (Game::with_seed(frame, 42), Game::with_seed(frame, 42));
+    for step in 0..1000 {
+        a.step(scripted_cursor(step), TIME_STEP);
+        b.step(scripted_cursor(step), TIME_STEP);
+        assert!(
+            a.rope.particles == b.rope.particles,
+            "ropes split on step {}",
+            step
+        );
+        assert_eq!(a.enemies.len(), b.enemies.len());
+        assert!(a
+            .enemies
+            .iter()
+            .zip(&b.enemies)
+            .all(|(a, b)| a.particle == b.particle));
+    }
+    assert!(!a.enemies.is_empty() || a.destroyed + a.escaped > 0);
+    assert_eq!(a.score, b.score);
+}
+
+#[test]
+fn dynamic_stiffness_is_tighter_in_fast_motion() {
+    // Mean segment error over two seconds of dragging the anchor back and
+    // forth at `speed` per frame, under gravity.
+    let mean_error = |dynamic_stiffness: bool, speed: f32| {
+        let mut game = game();
+        game.config.gravity = 800.0;
+        game.rope.dynamic_stiffness = dynamic_stiffness;
+        let mut cursor = vec2(200.0, 200.0);
+        for _ in 0..60 {
+            game.step(cursor, TIME_STEP);
+        }
+        let mut total = 0.0;
+        for step in 0..120 {
+            cursor.x += if (step / 30) % 2 == 0 { speed } else { -speed };
+            game.step(cursor, TIME_STEP);
+            total += game.rope.max_segment_error();
+        }
+        total / 120.0
+    };
+    let (fast, resting) = (mean_error(true, 20.0), mean_error(true, 0.0));
+    assert!(
+        fast < resting,
+        "{} error when fast, {} at rest",
+        fast,
+        resting
+    );
+    assert!(fast < mean_error(false, 20.0));
+}
+
+#[test]
+fn wall_touch_ends_the_run_only_in_hardcore_mode() {
+    let push_tip_into_wall = |wall_death: bool| {
+        let mut game = game();
+        game.config.wall_death = wall_death;
+        game.rope.translate(vec2(ARENA_WIDTH - 120.0, 200.0));
+        let anchor = game.rope.particles[0].position;
+        for _ in 0..60 {
+            game.step(anchor + vec2(60.0, 0.0), TIME_STEP);
+        }
+        let tip = game.rope.particles[game.rope.particles.len() - 1].position;
+        assert!(game.frame.touches_wall(tip), "tip stopped at {}", tip);
+        game.game_over
+    };
+    assert!(push_tip_into_wall(true));
+    assert!(!push_tip_into_wall(false));
+}
+
+#[test]
+fn hits_shrink_a_length_budget_rope_to_game_over() {
+    let mut game = game();
+    game.config.length_budget = true;
+    game.config.enemy_speed = 0.0;
+    game.max_health = f32::MAX;
+    game.health = f32::MAX;
+    game.rope.translate(vec2(200.0, 200.0));
+    let mut lengths = vec![game.rope.particles.len()];
+    while !game.game_over {
+        assert!(lengths.len() <= ROPE_PARTICLES, "lengths {:?}", lengths);
+        let tip = game.rope.particles[game.rope.particles.len() - 1].position;
+        let anchor = game.rope.particles[0].position;
+        game.enemies
+            .push(Enemy::at(tip + vec2(0.0, 5.0), anchor, EnemyKind::Chaser));
+        hold(&mut game, 1);
+        game.enemies.clear();
+        lengths.push(game.rope.particles.len());
+        // Wait out the flash and the invulnerability window.
+        hold(&mut game, 90);
+    }
+    let expected: Vec<usize> = (1..=ROPE_PARTICLES).rev().collect();
+    assert_eq!(lengths, expected);
+}
+
+#[test]
+fn wave_schedule_spawns_in_order() {
+    let mut game = game();
+    game.set_spawning(true, false);
+    game.config.enemy_speed = 0.0;
+    game.config.point_spawn_interval = f32::INFINITY;
+    game.rope.translate(vec2(300.0, 200.0));
+    game.waves = vec![
+        Wave {
+            count: 2,
+            edge: SpawnEdge::Left,
+            interval: 0.5,
+        },
+        Wave {
+            count: 3,
+            edge: SpawnEdge::Right,
+            interval: 0.25,
+        },
+    ];
+    let mut edges = Vec::new();
+    for _ in 0..300 {
+        let before = game.enemies.len();
+        hold(&mut game, 1);
+        for enemy in &game.enemies[before..] {
+            edges.push(enemy.particle.position.x == game.frame.x);
+        }
+    }
+    assert_eq!(edges, [true, true, false, false, false]);
+    assert!(game.waves_finished());
+}
+
+#[test]
+fn rewinding_restores_an_earlier_step() {
+    let mut game = Game::with_seed(Frame::with_size(ARENA_WIDTH, ARENA_HEIGHT), 5);
+    for step in 0..200 {
+        game.step(scripted_cursor(step), TIME_STEP);
+    }
+    let snapshot = game.clone();
+    for step in 200..205 {
+        game.step(scripted_cursor(step), TIME_STEP);
+    }
+    for _ in 0..5 {
+        assert!(game.rewind());
+    }
+    assert!(game.rope.particles == snapshot.rope.particles);
+    assert_eq!(game.enemies.len(), snapshot.enemies.len());
+    assert!(game
+        .enemies
+        .iter()
+        .zip(&snapshot.enemies)
+        .all(|(a, b)| a.particle == b.particle));
+    assert_eq!(game.points.len(), snapshot.points.len());
+    assert_eq!(game.score, snapshot.score);
+    assert_eq!(game.health, snapshot.health);
+    assert_eq!(game.invuln_time, snapshot.invuln_time);
+    assert_eq!(game.run_step, snapshot.run_step);
+    assert_eq!(game.spawn_timer, snapshot.spawn_timer);
+    assert_eq!(game.recording.len(), snapshot.recording.len());
+}
+
+#[test]
+fn no_contact_damage_during_the_invulnerability_window() {
+    let mut game = game();
+    game.config.enemy_speed = 0.0;
+    game.rope.translate(vec2(200.0, 200.0));
+    // An enemy dropped onto the middle of the rope every frame.
+    let press = |game: &mut Game| {
+        let body = game.rope.particles[5].position;
+        let anchor = game.rope.particles[0].position;
+        game.enemies.clear();
+        game.enemies
+            .push(Enemy::at(body + vec2(0.0, 5.0), anchor, EnemyKind::Chaser));
+        let health = game.health;
+        hold(game, 1);
+        game.health < health
+    };
+    assert!(press(&mut game));
+    assert!(game.invuln_time > 0.0);
+    // Hit-stop frames don't count towards the window.
+    let window = (INVULN_DURATION / TIME_STEP) as usize + HIT_STOP_FRAMES;
+    for frame in 1..window - 1 {
+        assert!(!press(&mut game), "hurt {} frames after the hit", frame);
+    }
+    assert!(
+        (0..3).any(|_| press(&mut game)),
+        "still invulnerable after the window"
+    );
+}
+
+#[test]
+fn hazard_drains_health_only_while_the_rope_is_inside() {
+    let mut game = game();
+    game.rope.translate(vec2(200.0, 200.0));
+    game.hazards
+        .push(Hazard::new(game.rope.particles[5].position, 20.0));
+    let mut health = game.health;
+    for _ in 0..30 {
+        hold(&mut game, 1);
+        assert!(game.health < health);
+        health = game.health;
+    }
+    assert_eq!(game.hazard_dps(), HAZARD_DPS);
+
+    // Pull the rope out of the pool.
+    let anchor = game.rope.particles[0].position;
+    for _ in 0..60 {
+        game.step(anchor + vec2(0.0, 150.0), TIME_STEP);
+    }
+    assert_eq!(game.hazard_dps(), 0.0);
+    let health = game.health;
+    hold(&mut game, 30);
+    assert!(game.health > health);
+}
+
+#[test]
+fn a_huge_impulse_tears_the_rope() {
+    let run = |tear_threshold: Option<f32>| {
+        let mut game = game();
+        game.config.tear_threshold = tear_threshold;
+        game.rope.translate(vec2(200.0, 200.0));
+        game.rope.particles[6].apply_impulse(vec2(0.0, 300.0));
+        hold(&mut game, 1);
+        game
+    };
+    let intact = run(None);
+    assert!(intact.torn_pieces.is_empty());
+    assert_eq!(intact.rope.particles.len(), ROPE_PARTICLES);
+    let worst = intact.rope.overstretched_segment(1.0).unwrap();
+
+    let torn = run(Some(1.0));
+    assert_eq!(torn.torn_pieces.len(), 1);
+    let piece = &torn.torn_pieces[0];
+    assert!(!piece.anchored);
+    assert_eq!(torn.rope.particles.len(), worst + 1);
+    assert_eq!(piece.particles.len(), ROPE_PARTICLES - worst - 1);
+}
+
+#[test]
+fn threat_rises_with_more_and_closer_enemies() {
+    let mut game = game();
+    let anchor = game.rope.particles[0].position;
+    assert_eq!(game.threat_level(), 0.0);
+    let mut threat = 0.0;
+    for i in 0..3 {
+        let position = vec2(700.0, 100.0 + i as f32 * 100.0);
+        game.enemies
+            .push(Enemy::at(position, anchor, EnemyKind::Chaser));
+        assert!(game.threat_level() > threat);
+        threat = game.threat_level();
+    }
+    game.enemies[0].particle.position = anchor + vec2(50.0, 50.0);
+    assert!(game.threat_level() > threat);
+}
+
+#[test]
+fn targets_hold_through_every_substep() {
+    let mut game = game();
+    game.config.gravity = 800.0;
+    let (left, right) = (vec2(200.0, 200.0), vec2(270.0, 200.0));
+    game.rope.translate(left - game.rope.particles[0].position);
+    let last = game.rope.particles.len() - 1;
+    for _ in 0..120 {
+        game.rope.set_targets(&[(0, left), (last, right)]);
+        game.step(left, TIME_STEP);
+        assert_eq!(game.rope.particles[0].position, left);
+        assert_eq!(game.rope.particles[last].position, right);
+        assert!(game.rope.targets.is_empty());
+    }
+    // The slack middle sags between the two held ends.
+    assert!(game.rope.particles[last / 2].position.y > left.y + 10.0);
+}
+
+#[test]
+fn a_stuck_enemy_slides_to_the_chosen_end() {
+    let mut game = game();
+    game.config.gravity = 0.0;
+    game.rope.sticky = true;
+    game.rope.sticky_end = RopeEnd::Tip;
+    let anchor = game.rope.particles[0].position;
+    let body = game.rope.particles[3].position;
+    game.enemies
+        .push(Enemy::at(body + vec2(0.0, 5.0), anchor, EnemyKind::Chaser));
+    hold(&mut game, 1);
+    let mut parameter = game.enemies[0].stuck.expect("caught on contact");
+    assert!(parameter < 4.0);
+    for _ in 0..10 {
+        hold(&mut game, 1);
+        let next = game.enemies[0].stuck.unwrap();
+        assert!(next > parameter, "{} after {}", next, parameter);
+        parameter = next;
+    }
+    let end = game.rope.segment_count() as f32;
+    let steps = ((end - parameter) / (STICKY_SLIDE_SPEED * TIME_STEP)).ceil() as usize;
+    hold(&mut game, steps + 1);
+    assert!(game.enemies.iter().all(|enemy| !enemy.active));
+    assert_eq!(game.destroyed, 1);
+}
+
+#[test]
+fn an_idle_rope_settles_into_its_rest_pose() {
+    let mut game = game();
+    game.config.gravity = 0.0;
+    let pose = game.rope.hanging_pose();
+    game.rope.rest_pose = Some(pose.clone());
+    let error = |game: &Game| {
+        let anchor = game.rope.particles[0].position;
+        game.rope
+            .particles
+            .iter()
+            .zip(&pose)
+            .map(|(particle, offset)| particle.position.distance(anchor + *offset))
+            .fold(0.0, f32::max)
+    };
+    let start = error(&game);
+    hold(&mut game, ((REST_POSE_DELAY - 0.5) / TIME_STEP) as usize);
+    assert!(error(&game) > start - 1.0, "moved before the delay");
+    hold(&mut game, 600);
+    assert!(error(&game) < 1.0, "still {} off", error(&game));
+}
+
+#[test]
+fn disabled_spawning_still_runs_the_rope() {
+    let mut game = Game::with_seed(Frame::with_size(ARENA_WIDTH, ARENA_HEIGHT), 7);
+    game.set_spawning(false, true);
+    let interval = game.config.enemy_spawn_interval;
+    let steps = (interval * 10.0 / TIME_STEP) as usize;
+    let tip = game.rope.particles[9].position;
+    for step in 0..steps {
+        game.step(scripted_cursor(step), TIME_STEP);
+        assert!(game.enemies.is_empty());
+    }
+    assert!(game.rope.particles[9].position.distance(tip) > 50.0);
+
+    // Turned back on, the first enemy waits a full interval.
+    game.set_spawning(true, false);
+    let before = (interval / TIME_STEP) as usize - 2;
+    hold(&mut game, before);
+    assert!(game.enemies.is_empty());
+    hold(&mut game, 4);
+    assert_eq!(game.enemies.len(), 1);
+}
+
+#[test]
+fn a_flick_speeds_the_tip_towards_the_cursor() {
+    let tip_speed = |flicks: usize| {
+        let mut game = game();
+        hold(&mut game, 120);
+        let anchor = game.rope.particles[0].position;
+        let cursor = anchor + vec2(200.0, 0.0);
+        for _ in 0..flicks {
+            game.flick(cursor);
+        }
+        game.step(anchor, TIME_STEP);
+        let tip = &game.rope.particles[game.rope.particles.len() - 1];
+        (tip.position - tip.old_position).x
+    };
+    let (still, flicked) = (tip_speed(0), tip_speed(1));
+    assert!(
+        flicked > still + 1.0,
+        "{} flicked, {} still",
+        flicked,
+        still
+    );
+    // A second click inside the cooldown adds nothing.
+    assert_eq!(tip_speed(2), flicked);
+}
+
+#[test]
+fn a_repulse_pickup_pushes_near_enemies_hardest() {
+    let mut game = game();
+    game.config.enemy_speed = 0.0;
+    let anchor = vec2(400.0, 300.0);
+    game.rope
+        .translate(anchor - game.rope.particles[0].position);
+    let mut point = Point::new(game.frame, &mut Rng::new(0));
+    point.position = anchor;
+    point.kind = PointKind::Repulse;
+    game.points.push(point);
+    let distances = [40.0, 80.0, 160.0, REPULSE_RADIUS + 50.0];
+    for distance in distances {
+        game.enemies.push(Enemy::at(
+            anchor - vec2(0.0, distance),
+            anchor,
+            EnemyKind::Chaser,
+        ));
+    }
+    hold(&mut game, 1);
+    assert_eq!(game.score, 1);
+    // Upwards is away from the pickup.
+    let outward: Vec<f32> = game
+        .enemies
+        .iter()
+        .map(|enemy| enemy.particle.old_position.y - enemy.particle.position.y)
+        .collect();
+    assert!(outward[0] > outward[1] && outward[1] > outward[2] && outward[2] > 0.0);
+    assert!((outward[0] / outward[1] - 2.0).abs() < 0.1, "{:?}", outward);
+    assert!(outward[3].abs() < 1e-3);
+}
+
+#[test]
+fn rope_colour_changes_at_each_score_tier() {
+    let mut game = game();
+    assert_eq!(game.color_for_score(-1), game.theme.rope);
+    let tiers = game.score_tiers.clone();
+    assert!(!tiers.is_empty());
+    for (threshold, color) in tiers {
+        assert_ne!(game.color_for_score(threshold - 1), color);
+        assert_eq!(game.color_for_score(threshold), color);
+    }
+    game.score_tiers = vec![(3, RED), (7, BLUE)];
+    assert_eq!(game.color_for_score(2), game.theme.rope);
+    assert_eq!(game.color_for_score(3), RED);
+    assert_eq!(game.color_for_score(6), RED);
+    assert_eq!(game.color_for_score(7), BLUE);
+    assert_eq!(game.color_for_score(1000), BLUE);
+}
+
+#[test]
+fn each_collected_point_brings_an_enemy() {
+    let mut game = Game::with_seed(Frame::with_size(ARENA_WIDTH, ARENA_HEIGHT), 7);
+    game.config.enemy_spawn_interval = f32::INFINITY;
+    game.config.point_spawn_interval = f32::INFINITY;
+    game.config.spawn_on_collect = 1;
+    game.points.clear();
+    hold(&mut game, 30);
+    assert!(game.enemies.is_empty());
+    for _ in 0..3 {
+        let anchor = game.rope.particles[0].position;
+        let mut point = Point::new(game.frame, &mut Rng::new(0));
+        point.position = anchor;
+        game.points.push(point);
+        hold(&mut game, 2);
+    }
+    assert_eq!(game.score, 3);
+    assert_eq!(game.enemies.len(), 3);
+}
+
+#[test]
+fn trailers_drop_slow_fields_that_expire() {
+    let mut game = game();
+    game.config.detection_radius = f32::INFINITY;
+    let anchor = game.rope.particles[0].position;
+    game.enemies
+        .push(Enemy::at(vec2(700.0, 500.0), anchor, EnemyKind::Trailer));
+    let fields = |game: &Game| -> Vec<Vec2> {
+        game.hazards
+            .iter()
+            .filter(|hazard| hazard.lifetime.is_some())
+            .map(|hazard| hazard.position)
+            .collect()
+    };
+    hold(
+        &mut game,
+        (TRAILER_DROP_INTERVAL * 3.5 / TIME_STEP) as usize,
+    );
+    let dropped = fields(&game);
+    assert_eq!(dropped.len(), 3);
+    // Along its path, not in one spot.
+    assert!(dropped[0].distance(dropped[2]) > SLOW_FIELD_RADIUS);
+
+    // Fields that outlive their duration go, however many keep coming.
+    hold(&mut game, (SLOW_FIELD_DURATION / TIME_STEP) as usize);
+    let most = (SLOW_FIELD_DURATION / TRAILER_DROP_INTERVAL).ceil() as usize;
+    assert!(fields(&game).len() <= most);
+    assert!(!fields(&game).contains(&dropped[0]));
+    game.enemies.clear();
+    hold(&mut game, (SLOW_FIELD_DURATION / TIME_STEP) as usize + 1);
+    assert!(fields(&game).is_empty());
+}
+
+#[test]
+fn spawns_follow_simulated_time_at_double_speed() {
+    let spawn_times = |time_scale: f32| {
+        let mut game = Game::with_seed(Frame::with_size(ARENA_WIDTH, ARENA_HEIGHT), 7);
+        game.config.enemy_speed = 0.0;
+        game.config.point_spawn_interval = f32::INFINITY;
+        let interval = game.config.enemy_spawn_interval;
+        let dt = TIME_STEP * time_scale;
+        let mut times = Vec::new();
+        let mut elapsed = 0.0;
+        while elapsed < interval * 5.5 {
+            let before = game.enemies.len();
+            hold_for(&mut game, dt);
+            elapsed += dt;
+            if game.enemies.len() > before {
+                times.push(elapsed);
+            }
+        }
+        (times, interval, dt)
+    };
+    for time_scale in [1.0, 2.0] {
+        let (times, interval, dt) = spawn_times(time_scale);
+        assert_eq!(times.len(), 5, "{:?} at {}x", times, time_scale);
+        for (k, time) in times.iter().enumerate() {
+            let due = interval * (k + 1) as f32;
+            assert!(*time >= due - 1e-3 && *time < due + dt + 1e-3);
+        }
+    }
+}
+
+#[test]
+fn point_callbacks_fire_on_every_pickup() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    let mut game = game();
+    let (first, second) = (Arc::new(AtomicUsize::new(0)), Arc::new(AtomicUsize::new(0)));
+    for counter in [&first, &second] {
+        let counter = Arc::clone(counter);
+        game.on_point_collected(Box::new(move |_| {
+            counter.fetch_add(1, Ordering::Relaxed);
+        }));
+    }
+    for pickups in 1..=3 {
+        let anchor = game.rope.particles[0].position;
+        let mut point = Point::new(game.frame, &mut Rng::new(0));
+        point.position = anchor;
+        game.points.push(point);
+        hold(&mut game, 2);
+        assert_eq!(first.load(Ordering::Relaxed), pickups);
+        assert_eq!(second.load(Ordering::Relaxed), pickups);
+    }
+}
+
+#[test]
+fn each_new_wave_raises_a_banner() {
+    let mut game = game();
+    game.set_spawning(true, false);
+    game.config.enemy_speed = 0.0;
+    game.config.point_spawn_interval = f32::INFINITY;
+    let wave = |count: usize| Wave {
+        count,
+        edge: SpawnEdge::Any,
+        interval: 0.5,
+    };
+    game.waves = vec![wave(2), wave(3)];
+    // Each banner text with the number of enemies out before it went up.
+    let mut banners: Vec<(String, usize)> = Vec::new();
+    for _ in 0..300 {
+        let before = game.enemies.len();
+        hold(&mut game, 1);
+        if let Some(banner) = &game.banner {
+            if banners.last().is_none_or(|(text, _)| *text != banner.text) {
+                banners.push((banner.text.clone(), before));
+            }
+        }
+    }
+    // Each banner goes up with its wave's first enemy.
+    assert_eq!(
+        banners,
+        [("Wave 1".to_string(), 0), ("Wave 2".to_string(), 2)]
+    );
+}
+
+#[test]
+fn a_square_lasso_scores_its_area() {
+    let lasso_score = |closed: bool| {
+        let mut game = game();
+        game.config.gravity = 0.0;
+        // Slow enough to stay under the score that grows the rope.
+        game.config.lasso_score_rate = 1.0;
+        // A 100 px square, one `LASSO_AREA_UNIT`, in 10 px segments.
+        let corner = vec2(300.0, 200.0);
+        let side = |i: usize| {
+            let (edge, along) = (i / 10, (i % 10) as f32 * SEGMENT_LENGTH);
+            match edge {
+                0 => vec2(along, 0.0),
+                1 => vec2(100.0, along),
+                2 => vec2(100.0 - along, 100.0),
+                _ => vec2(0.0, 100.0 - along),
+            }
+        };
+        game.rope.particles = (0..40).map(|i| Particle::new(corner + side(i))).collect();
+        game.rope.closed = closed;
+        game.rope.area_stiffness = 0.0;
+        assert_eq!(game.rope.area().abs(), LASSO_AREA_UNIT);
+        hold(&mut game, (4.5 / TIME_STEP) as usize);
+        game.score as f32 + game.lasso_carry
+    };
+    let score = lasso_score(true);
+    assert!((score - 4.5).abs() < 0.01, "{}", score);
+    assert_eq!(lasso_score(false), 0.0);
+}
+
+#[test]
+fn a_stalled_frame_advances_at_most_the_clamp() {
+    let stalled = |clamp: bool| {
+        let mut game = Game::with_seed(Frame::with_size(ARENA_WIDTH, ARENA_HEIGHT), 7);
+        game.config.enemy_spawn_interval = 0.5;
+        game.config.point_spawn_interval = f32::INFINITY;
+        let dt = if clamp {
+            game.config.frame_dt(1.0)
+        } else {
+            1.0
+        };
+        hold_for(&mut game, dt);
+        game
+    };
+    let clamped = stalled(true);
+    assert_eq!(clamped.spawn_timer, MAX_FRAME_DT);
+    assert!(clamped.enemies.is_empty());
+    // `Game::step` itself runs whatever it is given, but only the timers
+    // read it: the rope moves one fixed step either way.
+    let raw = stalled(false);
+    assert_eq!(raw.enemies.len(), 1);
+    assert!(raw.rope.particles == clamped.rope.particles);
+}
+
+#[test]
+fn a_capped_loop_sleeps_only_when_early() {
+    assert_eq!(frame_cap_delay(None, 0.001), None);
+    // 60 fps leaves 1/60 s per frame; a 5 ms frame sleeps for the rest.
+    let delay = frame_cap_delay(Some(60), 0.005).unwrap();
+    assert!((delay.as_secs_f64() - (1.0 / 60.0 - 0.005)).abs() < 1e-9);
+    assert_eq!(frame_cap_delay(Some(60), 0.02), None);
+}
+
+#[test]
+fn kills_throw_sparks_that_burn_out() {
+    // A sticky rope kills an enemy caught near its tip within a few frames.
+    let killed = |sparks_per_kill: u32| {
+        let mut game = game();
+        game.config.gravity = 0.0;
+        game.config.sparks_per_kill = sparks_per_kill;
+        game.rope.sticky = true;
+        let anchor = game.rope.particles[0].position;
+        let near_tip = game.rope.particles[8].position + vec2(0.0, 5.0);
+        game.enemies
+            .push(Enemy::at(near_tip, anchor, EnemyKind::Chaser));
+        for _ in 0..60 {
+            hold(&mut game, 1);
+            if game.destroyed > 0 {
+                break;
+            }
+        }
+        assert_eq!(game.destroyed, 1);
+        game
+    };
+    let mut game = killed(SPARKS_PER_KILL);
+    assert_eq!(game.sparks.len(), SPARKS_PER_KILL as usize);
+    let tip = game.rope.particles[9].position;
+    let start: Vec<f32> = game
+        .sparks
+        .iter()
+        .map(|spark| spark.pos.distance(tip))
+        .collect();
+    hold(&mut game, 5);
+    for (spark, start) in game.sparks.iter().zip(start) {
+        assert!(spark.pos.distance(tip) > start, "a spark fell back");
+    }
+    hold(&mut game, (SPARK_LIFETIME / TIME_STEP) as usize);
+    assert!(game.sparks.is_empty());
+
+    assert_eq!(killed(SPARK_MAX as u32 + 10).sparks.len(), SPARK_MAX);
+}
@@ -0,0 +1,74 @@
+//! Whole runs of the simulation with nothing drawn. These are what a
+//! server build relies on, so they must pass under
+//! `cargo test --no-default-features` as well as with rendering on.
+
+use macroquad::prelude::*;
+use sketch::*;
+
+/// A seeded game with the shield and the whip in play, stepped for
+/// `steps` frames along a fixed cursor path.
+fn busy_run(seed: u64, steps: usize) -> Game {
+    let mut game = Game::with_seed(Frame::with_size(ARENA_WIDTH, ARENA_HEIGHT), seed);
+    game.toggle_shield();
+    game.config.gravity = 300.0;
+    for step in 0..steps {
+        let angle = step as f32 * 0.03;
+        let cursor = vec2(400.0, 300.0) + vec2(angle.cos() * 250.0, (angle * 2.0).sin() * 150.0);
+        if step % 120 == 0 {
+            game.crack_whip();
+        }
+        game.step(cursor, TIME_STEP);
+        if game.game_over {
+            game.reset();
+        }
+    }
+    game
+}
+
+#[test]
+fn seeded_runs_step_identically() {
+    let (a, b) = (busy_run(3, 2000), busy_run(3, 2000));
+    assert!(a.rope.particles == b.rope.particles);
+    assert!(a.shield.unwrap().particles == b.shield.unwrap().particles);
+    assert_eq!(a.score, b.score);
+    assert_eq!(a.destroyed, b.destroyed);
+    assert_eq!(a.run_step, b.run_step);
+}
+
+#[test]
+fn a_long_run_stays_finite() {
+    let game = busy_run(9, 3000);
+    assert!(game
+        .rope
+        .particles
+        .iter()
+        .all(|particle| particle.position.is_finite()));
+    assert!(game.health.is_finite());
+}
+
+#[test]
+fn demo_ai_steers_the_anchor_to_points() {
+    let mut demo = DemoMode::new(Frame::with_size(ARENA_WIDTH, ARENA_HEIGHT));
+    demo.game.set_spawning(false, true);
+    demo.game.points.clear();
+    let target = vec2(600.0, 450.0);
+    let mut point = Point::new(demo.game.frame, &mut Rng::new(0));
+    point.position = target;
+    demo.game.points.push(point);
+    let distance = |demo: &DemoMode| demo.game.rope.particles[0].position.distance(target);
+    let mut last = distance(&demo);
+    for _ in 0..10 {
+        for _ in 0..6 {
+            demo.step(TIME_STEP);
+        }
+        if demo.game.score > 0 {
+            break;
+        }
+        assert!(distance(&demo) < last);
+        last = distance(&demo);
+    }
+    for _ in 0..300 {
+        demo.step(TIME_STEP);
+    }
+    assert_eq!(demo.game.score, 1);
+}
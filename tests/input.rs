@@ -0,0 +1,36 @@
+//! Cursor handling ahead of `Game::step`.
+
+use macroquad::prelude::*;
+use sketch::*;
+
+#[test]
+fn filters_steady_a_shaky_cursor() {
+    // Mean squared jump between frames of `filter` over a cursor shaking
+    // around one spot.
+    let jitter = |filter: TargetFilter| {
+        let mut smoother = TargetSmoother::new(filter);
+        let mut rng = Rng::new(5);
+        let mut last = None;
+        let mut total = 0.0;
+        let frames = 600;
+        for _ in 0..frames {
+            let shake = vec2(rng.range(-10.0, 10.0), rng.range(-10.0, 10.0));
+            let target = smoother.apply(vec2(400.0, 300.0) + shake, TIME_STEP);
+            if let Some(last) = last {
+                total += target.distance_squared(last);
+            }
+            last = Some(target);
+        }
+        total / (frames - 1) as f32
+    };
+    let raw = jitter(TargetFilter::None);
+    let average = jitter(TargetFilter::MovingAverage {
+        n: TARGET_AVERAGE_WINDOW,
+    });
+    let one_euro = jitter(TargetFilter::OneEuro {
+        min_cutoff: ONE_EURO_MIN_CUTOFF,
+        beta: ONE_EURO_BETA,
+    });
+    assert!(average < raw * 0.5, "{} averaged, {} raw", average, raw);
+    assert!(one_euro < raw * 0.5, "{} one-euro, {} raw", one_euro, raw);
+}
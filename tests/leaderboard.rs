@@ -0,0 +1,52 @@
+//! The local leaderboard and its file format.
+
+use sketch::*;
+
+#[test]
+fn scores_stay_sorted_and_trimmed() {
+    let mut leaderboard = Leaderboard::default();
+    for (i, score) in [30, 10, 50, 20, 40].into_iter().enumerate() {
+        leaderboard.insert(score, i as f64);
+    }
+    let scores = |leaderboard: &Leaderboard| -> Vec<i32> {
+        leaderboard
+            .entries
+            .iter()
+            .map(|entry| entry.score)
+            .collect()
+    };
+    assert_eq!(scores(&leaderboard), [50, 40, 30, 20, 10]);
+    // A tie goes below the score already there.
+    assert_eq!(leaderboard.insert(30, 9.0), Some(3));
+    assert_eq!(leaderboard.entries[2].timestamp, 0.0);
+
+    for score in 100..LEADERBOARD_SIZE as i32 + 100 {
+        leaderboard.insert(score, 0.0);
+    }
+    assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+    assert_eq!(leaderboard.entries[0].score, LEADERBOARD_SIZE as i32 + 99);
+    assert_eq!(leaderboard.insert(5, 0.0), None);
+    assert_eq!(leaderboard.entries.len(), LEADERBOARD_SIZE);
+}
+
+#[test]
+fn leaderboards_survive_the_file_format() {
+    let mut leaderboard = Leaderboard::default();
+    leaderboard.insert(12, 1_700_000_000.5);
+    leaderboard.insert(7, 1_700_000_100.0);
+    assert_eq!(
+        Leaderboard::parse(&leaderboard.to_json()),
+        Some(leaderboard)
+    );
+    assert_eq!(Leaderboard::parse("[]"), Some(Leaderboard::default()));
+    assert_eq!(Leaderboard::parse("[{\"score\": 3"), None);
+    assert_eq!(Leaderboard::parse("{\"score\": 3}"), None);
+    // Out of order in the file, sorted on the way in.
+    let parsed =
+        Leaderboard::parse("[{\"score\": 1, \"timestamp\": 0}, {\"score\": 9, \"timestamp\": 0}]")
+            .unwrap();
+    assert_eq!(parsed.entries[0].score, 9);
+    assert!(Leaderboard::load("/nonexistent/leaderboard.json")
+        .entries
+        .is_empty());
+}
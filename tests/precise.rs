@@ -0,0 +1,33 @@
+//! The double-precision rope against the `f32` one. Only built with
+//! `--features f64`.
+#![cfg(feature = "f64")]
+
+use macroquad::prelude::*;
+use sketch::precise::*;
+use sketch::*;
+
+#[test]
+fn f64_rope_holds_segments_tighter_far_from_the_origin() {
+    // 100 000 px out, neighbouring `f32` values are 1/128 px apart, so
+    // rounding adds to the solver's own stretch. Near the origin the
+    // solver's few iterations dominate and the two paths stretch alike.
+    let anchor = vec2(100_400.0, 100_050.0);
+    let mut rope = Rope::new(anchor, 201);
+    let mut precise = PreciseRope::from_rope(&rope);
+    let (mut single, mut double): (f32, f64) = (0.0, 0.0);
+    for step in 0..10_000 {
+        let target = anchor + vec2((step as f32 * 0.01).sin() * 100.0, 0.0);
+        rope.update(target);
+        precise.update(target);
+        single = single.max(rope.max_segment_error());
+        double = double.max(precise.max_segment_error());
+    }
+    assert!(single.is_finite() && double.is_finite());
+    // Measured: about 2.9 in f32 against 1.6 in f64.
+    assert!(
+        double < single as f64 * 0.8,
+        "{} in f32, {} in f64",
+        single,
+        double
+    );
+}
@@ -0,0 +1,619 @@
+//! Rope physics and geometry, run headless.
+
+use macroquad::prelude::*;
+use sketch::*;
+
+#[test]
+fn reset_matches_a_new_rope() {
+    let mut rope = Rope::new(vec2(300.0, 200.0), 12);
+    rope.gravity = 500.0;
+    rope.pin(5, vec2(320.0, 180.0));
+    rope.constraint_strength = 1.2;
+    rope.closed = true;
+    rope.anchored = false;
+    rope.last_recovery = Some(Recovery {
+        update: 3,
+        particles: 1,
+    });
+    for i in 0..60 {
+        rope.update(vec2(300.0 + i as f32 * 4.0, 200.0));
+    }
+
+    rope.reset(ROPE_START);
+    let fresh = Rope::new(ROPE_START, 12);
+    assert!(rope.particles == fresh.particles);
+    assert!(rope.pins.is_empty());
+    assert_eq!(rope.segment_length, fresh.segment_length);
+    assert_eq!(rope.constraint_strength, fresh.constraint_strength);
+    assert_eq!(rope.thickness, fresh.thickness);
+    assert_eq!(rope.closed, fresh.closed);
+    assert_eq!(rope.anchored, fresh.anchored);
+    assert_eq!(rope.anchor_speed, fresh.anchor_speed);
+    assert_eq!(rope.update_count, fresh.update_count);
+    assert_eq!(rope.last_recovery, None);
+}
+
+/// A rope hanging under gravity for a while, so it is bent.
+fn hanging_rope(particles: usize) -> Rope {
+    let mut rope = Rope::new(vec2(300.0, 100.0), particles);
+    rope.gravity = 800.0;
+    for _ in 0..120 {
+        rope.update(vec2(300.0, 100.0));
+    }
+    rope
+}
+
+#[test]
+fn subdivide_keeps_length_and_ends() {
+    let mut rope = hanging_rope(10);
+    let length = rope.total_length();
+    let anchor = rope.particles[0].position;
+    let tip = rope.particles[9].position;
+
+    rope.subdivide();
+    assert_eq!(rope.particles.len(), 19);
+    assert!((rope.total_length() - length).abs() < 1e-3);
+    assert_eq!(rope.particles[0].position, anchor);
+    assert_eq!(rope.particles[18].position, tip);
+    assert_eq!(rope.segment_length, SEGMENT_LENGTH / 2.0);
+}
+
+/// Largest gap between particle `i` and the mirror image of particle
+/// `n - 1 - i` about the vertical line through the middle of the rope.
+fn mirror_error(rope: &Rope) -> f32 {
+    let n = rope.particles.len();
+    let middle = (rope.particles[0].position.x + rope.particles[n - 1].position.x) / 2.0;
+    (0..n)
+        .map(|i| {
+            let a = rope.particles[i].position;
+            let b = rope.particles[n - 1 - i].position;
+            vec2(2.0 * middle - b.x, b.y).distance(a)
+        })
+        .fold(0.0, f32::max)
+}
+
+/// Lopsidedness of a free rope stretched evenly from both ends after a
+/// few updates: any lean to one side comes from the solver.
+fn relax_stretched(solver: SolverKind, alternate_sweep: bool) -> f32 {
+    let mut rope = Rope::new(Vec2::ZERO, 11);
+    rope.anchored = false;
+    rope.solver = solver;
+    rope.alternate_sweep = alternate_sweep;
+    for (i, particle) in rope.particles.iter_mut().enumerate() {
+        *particle = Particle::new(vec2(
+            300.0 + i as f32 * 15.0,
+            100.0 + (i as f32 - 5.0).abs(),
+        ));
+    }
+    for _ in 0..3 {
+        rope.update(Vec2::ZERO);
+    }
+    mirror_error(&rope)
+}
+
+#[test]
+fn jacobi_relaxes_symmetrically() {
+    let jacobi = relax_stretched(SolverKind::Jacobi, false);
+    let gauss_seidel = relax_stretched(SolverKind::GaussSeidel, false);
+    assert!(jacobi < 1e-3, "jacobi shape is lopsided by {}", jacobi);
+    assert!(
+        gauss_seidel > 0.5,
+        "gauss-seidel shape is lopsided by only {}",
+        gauss_seidel
+    );
+}
+
+#[test]
+fn integrators_agree_over_one_step() {
+    let kick = |integrator: Integrator| {
+        let mut rope = hanging_rope(10);
+        rope.set_integrator(integrator);
+        rope.particles[9].apply_impulse(vec2(6.0, -4.0));
+        rope.update(vec2(300.0, 100.0));
+        rope
+    };
+    let verlet = kick(Integrator::Verlet);
+    let euler = kick(Integrator::SemiImplicitEuler);
+    for (a, b) in verlet.particles.iter().zip(&euler.particles) {
+        assert!(
+            a.position.distance(b.position) < 1.0,
+            "{} against {}",
+            a.position,
+            b.position
+        );
+    }
+    // The kick itself is not lost.
+    let rest = hanging_rope(10).particles[9].position;
+    assert!(verlet.particles[9].position.distance(rest) > 3.0);
+}
+
+/// A resting rope through `positions`.
+fn shaped(positions: &[Vec2]) -> Rope {
+    let mut rope = Rope::new(Vec2::ZERO, positions.len());
+    for (particle, &position) in rope.particles.iter_mut().zip(positions) {
+        *particle = Particle::new(position);
+    }
+    rope
+}
+
+#[test]
+fn bounds_of_a_zigzag() {
+    let rope = shaped(&[
+        vec2(100.0, 100.0),
+        vec2(110.0, 140.0),
+        vec2(90.0, 120.0),
+        vec2(130.0, 80.0),
+    ]);
+    assert_eq!(rope.bounds(), (vec2(90.0, 80.0), vec2(130.0, 140.0)));
+    assert_eq!(
+        shaped(&[vec2(5.0, 6.0)]).bounds(),
+        (vec2(5.0, 6.0), vec2(5.0, 6.0))
+    );
+}
+
+#[test]
+fn mid_rope_pin_holds_while_the_rest_hangs() {
+    let mut rope = Rope::new(vec2(300.0, 100.0), 20);
+    rope.anchored = false;
+    rope.gravity = 800.0;
+    let pin = vec2(400.0, 100.0);
+    rope.pin(10, pin);
+    for _ in 0..300 {
+        rope.update(Vec2::ZERO);
+        assert_eq!(rope.particles[10].position, pin);
+    }
+    // Both halves hang straight down from the pin.
+    for i in [0, 19] {
+        let particle = rope.particles[i].position;
+        assert!(particle.y > pin.y + 50.0, "particle {} at {}", i, particle);
+        assert!(
+            (particle.x - pin.x).abs() < 10.0,
+            "particle {} at {}",
+            i,
+            particle
+        );
+    }
+}
+
+#[test]
+fn alternating_sweeps_even_out_a_symmetric_load() {
+    let one_way = relax_stretched(SolverKind::GaussSeidel, false);
+    let alternating = relax_stretched(SolverKind::GaussSeidel, true);
+    assert!(
+        alternating < one_way / 2.0,
+        "{} alternating against {} one way",
+        alternating,
+        one_way
+    );
+}
+
+#[test]
+fn particle_queries_by_index() {
+    let mut rope = Rope::new(vec2(300.0, 100.0), 5);
+    assert!(rope.particle(4).is_some());
+    assert!(rope.particle(5).is_none());
+    assert!(rope.particle_mut(5).is_none());
+    assert!(!rope.set_position(5, Vec2::ZERO, true));
+
+    rope.particles[2].apply_impulse(vec2(3.0, 1.0));
+    assert!(rope.set_position(2, vec2(320.0, 150.0), true));
+    let moved = rope.particle(2).unwrap();
+    assert_eq!(moved.position, vec2(320.0, 150.0));
+    assert_eq!(moved.position - moved.old_position, vec2(3.0, 1.0));
+
+    assert!(rope.set_position(2, vec2(330.0, 150.0), false));
+    let stopped = rope.particle(2).unwrap();
+    assert_eq!(stopped.position, vec2(330.0, 150.0));
+    assert_eq!(stopped.position, stopped.old_position);
+    assert_eq!(stopped.velocity, Vec2::ZERO);
+}
+
+/// A free closed loop of `n` particles laid on a circle at rest length.
+fn ring(n: usize) -> Rope {
+    let radius = SEGMENT_LENGTH / (2.0 * (std::f32::consts::PI / n as f32).sin());
+    let positions: Vec<Vec2> = (0..n)
+        .map(|i| {
+            vec2(400.0, 300.0)
+                + Vec2::from_angle(i as f32 / n as f32 * std::f32::consts::TAU) * radius
+        })
+        .collect();
+    let mut rope = shaped(&positions);
+    rope.anchored = false;
+    rope.closed = true;
+    rope
+}
+
+#[test]
+fn blob_keeps_its_area_when_poked() {
+    let poke = |area_stiffness: f32| {
+        let mut rope = ring(20);
+        rope.area_stiffness = area_stiffness;
+        let start = rope.area();
+        for i in 0..5 {
+            let inward = vec2(400.0, 300.0) - rope.particles[i].position;
+            rope.particles[i].apply_impulse(inward.normalize() * 3.0);
+        }
+        let mut worst: f32 = 0.0;
+        for _ in 0..120 {
+            rope.update(Vec2::ZERO);
+            worst = worst.max((rope.area() - start).abs() / start.abs());
+        }
+        worst
+    };
+    let blob = poke(BLOB_AREA_STIFFNESS);
+    let loose = poke(0.0);
+    assert!(blob < 0.1, "area strayed by {}", blob);
+    assert!(
+        blob < loose,
+        "{} with area stiffness, {} without",
+        blob,
+        loose
+    );
+}
+
+#[test]
+fn rope_settles_on_the_ground_and_stays() {
+    let ground = 150.0;
+    let mut rope = Rope::new(vec2(300.0, 100.0), 20);
+    rope.gravity = 800.0;
+    rope.ground_y = Some(ground);
+    for update in 0..600 {
+        rope.update(vec2(300.0, 100.0));
+        assert!(rope
+            .particles
+            .iter()
+            .all(|particle| particle.position.y <= ground));
+        if update == 300 {
+            let resting = rope
+                .particles
+                .iter()
+                .filter(|p| p.position.y >= ground - 0.5);
+            assert!(resting.count() >= 10);
+        }
+    }
+    let tip = rope.particles[19];
+    assert!((tip.position.y - ground).abs() < 0.5);
+    assert!(tip.position.distance(tip.old_position) < 0.05);
+}
+
+#[test]
+fn undamped_tumbling_rope_keeps_its_energy() {
+    let mut rope = Rope::new(vec2(300.0, 300.0), 11);
+    rope.anchored = false;
+    let centre = rope.particles[5].position;
+    for particle in &mut rope.particles {
+        particle.friction = 1.0;
+        // Drift while turning about the middle at 0.005 radians per step.
+        let spin = (particle.position - centre).perp() * 0.005;
+        particle.apply_impulse(spin + vec2(1.0, 0.5));
+    }
+    let start = rope.kinetic_energy();
+    for _ in 0..600 {
+        rope.update(Vec2::ZERO);
+        let energy = rope.kinetic_energy();
+        assert!(
+            (energy - start).abs() < start * 0.02,
+            "energy went from {} to {}",
+            start,
+            energy
+        );
+    }
+}
+
+#[test]
+fn a_moving_anchor_carries_only_its_latest_move() {
+    let mut rope = Rope::new(vec2(300.0, 300.0), 10);
+    for i in 1..=5 {
+        rope.update(vec2(300.0 + i as f32 * 2.0, 300.0));
+    }
+    let anchor = rope.particles[0];
+    assert_eq!(anchor.position - anchor.old_position, vec2(2.0, 0.0));
+}
+
+#[test]
+fn joined_ropes_settle_into_one() {
+    let mut rope = Rope::new(vec2(100.0, 100.0), 5);
+    rope.join(Rope::new(vec2(300.0, 300.0), 5));
+    assert_eq!(rope.particles.len(), 10);
+    assert!(
+        rope.strain(4).abs() < 1e-4,
+        "seam strain {}",
+        rope.strain(4)
+    );
+    assert!(rope.max_segment_error() < 1e-3);
+
+    // Once both have stopped swinging, it hangs just like a rope that was
+    // never joined.
+    let mut whole = Rope::new(vec2(100.0, 100.0), 10);
+    for rope in [&mut rope, &mut whole] {
+        rope.gravity = 800.0;
+        for _ in 0..1200 {
+            rope.update(vec2(100.0, 100.0));
+        }
+    }
+    for (joined, whole) in rope.particles.iter().zip(&whole.particles) {
+        assert!(
+            joined.position.distance(whole.position) < 0.5,
+            "{} against {}",
+            joined.position,
+            whole.position
+        );
+    }
+    let kink = rope.strain(4) - (rope.strain(3) + rope.strain(5)) / 2.0;
+    assert!(kink.abs() < 0.02, "seam kinked by {}", kink);
+}
+
+#[test]
+fn joining_an_empty_rope_keeps_the_other_as_it_was() {
+    let mut empty = Rope::new(vec2(100.0, 100.0), 5);
+    empty.particles.clear();
+    let rope = Rope::new(vec2(300.0, 300.0), 5);
+
+    let mut onto_empty = empty.clone();
+    onto_empty.join(rope.clone());
+    let mut from_empty = rope.clone();
+    from_empty.join(empty);
+    for joined in [&onto_empty, &from_empty] {
+        assert_eq!(joined.particles.len(), 5);
+        for (joined, original) in joined.particles.iter().zip(&rope.particles) {
+            assert_eq!(joined.position, original.position);
+        }
+    }
+}
+
+#[test]
+fn closing_a_hanging_rope_settles_into_a_stable_loop() {
+    let mut rope = hanging_rope(20);
+    rope.closed = true;
+    let rest_area = (20.0 * SEGMENT_LENGTH).powi(2) / (4.0 * std::f32::consts::PI);
+    let mut areas = Vec::new();
+    for _ in 0..600 {
+        rope.update(vec2(300.0, 100.0));
+        areas.push(rope.area().abs());
+    }
+    assert!(rope.particles.iter().all(|p| p.position.is_finite()));
+    // The seam joining tip to anchor stretches like its mirror image, the
+    // first segment: the loop hangs evenly from the anchor.
+    let (seam, first) = (rope.strain(19), rope.strain(0));
+    assert!(
+        (seam - first).abs() < 0.05,
+        "seam strain {} against {}",
+        seam,
+        first
+    );
+    let settled = &areas[300..];
+    let (low, high) = settled.iter().fold((f32::MAX, 0.0f32), |(low, high), &a| {
+        (low.min(a), high.max(a))
+    });
+    assert!(
+        high - low < rest_area * 0.05,
+        "area swings from {} to {}",
+        low,
+        high
+    );
+}
+
+#[test]
+fn curvature_of_an_l_shape() {
+    // Three segments right, then three down: a right angle at particle 3.
+    let positions: Vec<Vec2> = (0..4)
+        .map(|i| vec2(100.0 + i as f32 * 10.0, 100.0))
+        .chain((1..4).map(|i| vec2(130.0, 100.0 + i as f32 * 10.0)))
+        .collect();
+    let (index, angle) = shaped(&positions).max_curvature();
+    assert_eq!(index, 3);
+    assert!((angle - std::f32::consts::FRAC_PI_2).abs() < 1e-5);
+
+    let straight = Rope::new(vec2(100.0, 100.0), 7);
+    assert_eq!(straight.max_curvature().1, 0.0);
+}
+
+#[test]
+fn arc_length_samples_along_uneven_segments() {
+    // Segments of 10, 30 and 20 along an L.
+    let rope = shaped(&[
+        vec2(0.0, 0.0),
+        vec2(10.0, 0.0),
+        vec2(40.0, 0.0),
+        vec2(40.0, 20.0),
+    ]);
+    let total = rope.total_length();
+    assert_eq!(total, 60.0);
+    assert_eq!(rope.point_at_arc_length(0.0), vec2(0.0, 0.0));
+    assert_eq!(rope.point_at_arc_length(total), vec2(40.0, 20.0));
+    assert_eq!(rope.point_at_arc_length(total / 2.0), vec2(30.0, 0.0));
+    assert_eq!(rope.point_at_arc_length(50.0), vec2(40.0, 10.0));
+    assert_eq!(rope.point_at_arc_length(-5.0), vec2(0.0, 0.0));
+    assert_eq!(rope.point_at_arc_length(total + 5.0), vec2(40.0, 20.0));
+}
+
+#[test]
+fn safe_mode_recovers_a_nan_particle() {
+    let mut rope = hanging_rope(10);
+    let anchor = rope.particles[0].position;
+    rope.particles[5].position = vec2(f32::NAN, 0.0);
+    rope.update(anchor);
+    let update = rope.update_count;
+    assert!(rope
+        .particles
+        .iter()
+        .all(|particle| particle.position.is_finite()));
+    assert!(rope
+        .last_recovery
+        .is_some_and(|recovery| recovery.update == update));
+    for _ in 0..120 {
+        rope.update(anchor);
+    }
+    assert!(rope
+        .particles
+        .iter()
+        .all(|particle| particle.position.is_finite()));
+    // Nothing went bad again, and the rope didn't collapse onto the anchor.
+    assert_eq!(rope.last_recovery.unwrap().update, update);
+    assert!(rope.particles[9].position.distance(anchor) > 8.0 * SEGMENT_LENGTH);
+
+    let mut unsafe_rope = hanging_rope(10);
+    unsafe_rope.safe_mode = false;
+    unsafe_rope.particles[5].position = vec2(f32::NAN, 0.0);
+    unsafe_rope.update(anchor);
+    assert!(unsafe_rope.last_recovery.is_none());
+    assert!(unsafe_rope.particles[9].position.is_nan());
+}
+
+#[test]
+fn presets_round_trip() {
+    let mut rope = shaped(&[
+        vec2(0.0, 0.0),
+        vec2(12.5, -3.0),
+        vec2(20.0, 7.25),
+        vec2(31.0, 4.0),
+    ]);
+    rope.solver = SolverKind::Jacobi;
+    rope.integrator = Integrator::SemiImplicitEuler;
+    rope.closed = true;
+    rope.thickness = 3.5;
+    rope.segment_length = 12.0;
+    rope.bead_spacing = Some(2);
+    rope.pin(2, vec2(20.0, 7.25));
+    let preset = rope.export_preset();
+    let copy = Rope::import_preset(&preset).unwrap();
+    assert!(copy.particles == rope.particles);
+    assert_eq!(copy.pins, rope.pins);
+    assert_eq!(copy.solver, rope.solver);
+    assert_eq!(copy.integrator, rope.integrator);
+    assert!(copy.closed);
+    assert_eq!(copy.thickness, 3.5);
+    assert_eq!(copy.segment_length, 12.0);
+    assert_eq!(copy.bead_spacing, Some(2));
+    assert_eq!(copy.export_preset(), preset);
+}
+
+#[test]
+fn bad_presets_are_refused() {
+    let preset = Rope::new(Vec2::ZERO, 5).export_preset();
+    assert!(Rope::import_preset("not a preset!").is_err());
+    assert!(Rope::import_preset(&preset[..preset.len() - 8]).is_err());
+
+    let refused = |edit: fn(&mut Rope)| {
+        let mut rope = Rope::new(Vec2::ZERO, 5);
+        edit(&mut rope);
+        Rope::import_preset(&rope.export_preset()).is_err()
+    };
+    assert!(refused(|rope| rope.particles[2].position.x = f32::NAN));
+    assert!(refused(|rope| rope.particles[4].position.y = f32::INFINITY));
+    assert!(refused(|rope| rope.constraint_strength = f32::NAN));
+    assert!(refused(|rope| rope.segment_length = 0.0));
+    assert!(refused(|rope| rope.thickness = -1.0));
+    assert!(refused(|rope| rope.ball_radius = 0.0));
+    assert!(refused(|rope| rope.speed_glow = -0.5));
+    assert!(refused(|rope| {
+        rope.pins.insert(1, vec2(f32::NAN, 0.0));
+    }));
+    assert!(!refused(|_| {}));
+}
+
+#[test]
+fn a_velocity_cap_bounds_a_teleport() {
+    let fastest = |max_velocity: Option<f32>| {
+        let mut rope = hanging_rope(10);
+        rope.max_velocity = max_velocity;
+        let far = vec2(800.0, 400.0);
+        let mut fastest: f32 = 0.0;
+        for _ in 0..30 {
+            rope.update(far);
+            for particle in &rope.particles[1..] {
+                fastest = fastest.max((particle.position - particle.old_position).length());
+            }
+        }
+        fastest
+    };
+    assert!(fastest(None) > 40.0);
+    assert!(fastest(Some(20.0)) <= 20.0 + 1e-3);
+}
+
+#[test]
+fn growing_a_loop_keeps_it_shut() {
+    let mut rope = ring(20);
+    let (tip, anchor) = (rope.particles[19].position, rope.particles[0].position);
+    rope.append_segment();
+    assert_eq!(rope.particles.len(), 21);
+    assert_eq!(rope.particles[20].position, tip.lerp(anchor, 0.5));
+    for _ in 0..60 {
+        rope.update(anchor);
+    }
+    assert!(rope.max_segment_error() < 1.0);
+
+    let mut open = Rope::new(Vec2::ZERO, 3);
+    open.append_segment();
+    assert_eq!(open.particles[3].position, vec2(3.0 * SEGMENT_LENGTH, 0.0));
+}
+
+#[test]
+fn center_of_mass_weighs_heavy_particles() {
+    let square = [
+        vec2(0.0, 0.0),
+        vec2(40.0, 0.0),
+        vec2(40.0, 40.0),
+        vec2(0.0, 40.0),
+    ];
+    let mut rope = shaped(&square);
+    assert_eq!(rope.center_of_mass(), vec2(20.0, 20.0));
+    // Three times the mass on the right-hand side.
+    rope.particles[1].mass = 3.0;
+    rope.particles[2].mass = 3.0;
+    rope.particles[0].mass = 1.0;
+    rope.particles[3].mass = 1.0;
+    assert_eq!(rope.center_of_mass(), vec2(30.0, 20.0));
+    for particle in &mut rope.particles {
+        particle.mass = 0.0;
+    }
+    assert_eq!(rope.center_of_mass(), vec2(20.0, 20.0));
+
+    let single = shaped(&[vec2(7.0, 9.0)]);
+    assert_eq!(single.center_of_mass(), vec2(7.0, 9.0));
+    rope.particles.clear();
+    assert_eq!(rope.center_of_mass(), Vec2::ZERO);
+    assert_eq!(rope.average_velocity(), Vec2::ZERO);
+}
+
+#[test]
+fn average_velocity_of_a_drifting_and_a_settled_rope() {
+    let mut rope = Rope::new(vec2(100.0, 100.0), 10);
+    let drift = vec2(2.0, -1.0);
+    for particle in &mut rope.particles {
+        particle.old_position = particle.position - drift;
+    }
+    assert!(rope.average_velocity().distance(drift) < 1e-5);
+
+    // Long after a big anchor move the rope is only hanging. Each
+    // step's gravity shows as a sliver of downward velocity, nothing
+    // more.
+    let mut rope = hanging_rope(10);
+    for _ in 0..600 {
+        rope.update(vec2(600.0, 400.0));
+    }
+    let velocity = rope.average_velocity();
+    assert!(velocity.length() < 0.3, "{}", velocity);
+}
+
+#[test]
+fn zeroed_velocities_stay_zero_after_a_teleport() {
+    let fastest = |zero: bool| {
+        let mut rope = Rope::new(vec2(100.0, 100.0), 10);
+        // Moved outright to a new pose: vertical, somewhere else.
+        let anchor = vec2(500.0, 200.0);
+        for (i, particle) in rope.particles.iter_mut().enumerate() {
+            particle.position = anchor + vec2(0.0, i as f32 * SEGMENT_LENGTH);
+        }
+        if zero {
+            rope.zero_velocities();
+        }
+        rope.update(anchor);
+        rope.particles
+            .iter()
+            .map(|particle| (particle.position - particle.old_position).length())
+            .fold(0.0, f32::max)
+    };
+    assert!(fastest(false) > 100.0);
+    assert!(fastest(true) < 1e-3);
+}